@@ -10,91 +10,427 @@ use proc_macro2::{Span, TokenStream as TokenStream2};
 
 use proc_macro_error::{abort, proc_macro_error};
 use syn::{
-    punctuated::Punctuated, token::Comma, Field, GenericParam, Generics, Ident, Item, TraitBound,
-    TraitBoundModifier, Type, TypeParamBound, TypePath,
+    bracketed,
+    parse::{ParseStream, Parser},
+    punctuated::Punctuated,
+    token::Comma,
+    Attribute, Expr, ExprLit, Field, Fields, FnArg, GenericParam, Generics, Ident, Item, ItemFn,
+    Lit, Pat, Path, Token, TraitBound, TraitBoundModifier, Type, TypeParamBound,
 };
 
+/// Where the values of a field come from
 #[derive(Debug)]
-struct StructFieldGen {
-    name: Ident,
-    ty: TypePath,
+enum FieldSource {
+    /// Every variant of the field type
+    Variants,
+    /// The expressions listed in `#[every_variant(values = [...])]`, `partial_eq` when the
+    /// value of the field is looked up among them with `==`
+    Values { values: Vec<Expr>, partial_eq: bool },
+    /// The edge cases of the field type, from `#[every_variant(edge_cases)]`
+    EdgeCases,
+    /// A single value, from `#[every_variant(skip)]` or `#[every_variant(default = ...)]`
+    Skip(Box<Expr>),
 }
 
+/// A single field of a struct or enum variant, and the binding it gets in the generated code
 #[derive(Debug)]
-struct AnonStructFieldGen {
-    id: Ident,
-    ty: TypePath,
-}
-
-#[derive(Debug)]
-struct EnumFieldGen {
+struct FieldGen {
     id: Ident,
     ty: Type,
     name: Option<Ident>,
+    /// The name, or the position in a tuple, as written when accessing the field
+    member: String,
+    source: FieldSource,
 }
 
-fn do_enum_gen(var_id: &Ident, field_data: &Punctuated<Field, Comma>) -> TokenStream2 {
-    let mut field_gen = Vec::new();
-    for (idx, field) in field_data.iter().enumerate() {
-        field_gen.push(EnumFieldGen {
-            id: Ident::new(&format!("v{}", &idx.to_string()), Span::call_site()),
-            ty: field.ty.clone(),
-            name: field.ident.clone(),
-        });
+impl FieldGen {
+    /// Skipped fields are not iterated over, they are filled in by the constructor
+    fn is_skipped(&self) -> bool {
+        matches!(self.source, FieldSource::Skip(_))
+    }
+
+    /// Expression for the number of values of this field
+    fn count(&self) -> TokenStream2 {
+        match &self.source {
+            FieldSource::Variants => {
+                let ty = &self.ty;
+                quote! { <#ty as EveryVariant>::variant_count() }
+            }
+            FieldSource::Values { values, .. } => {
+                let len = values.len();
+                quote! { #len }
+            }
+            FieldSource::EdgeCases => {
+                let ty = &self.ty;
+                quote! { <#ty as ::every_variant::EdgeCases>::edge_cases().len() }
+            }
+            FieldSource::Skip(_) => quote! { 1usize },
+        }
+    }
+
+    /// Expression for an `Option` of the value of this field at `index`
+    fn nth(&self, index: &Ident) -> TokenStream2 {
+        match &self.source {
+            FieldSource::Variants => {
+                let ty = &self.ty;
+                quote! { <#ty as EveryVariant>::nth_variant(#index) }
+            }
+            _ => {
+                let iter = self.iter();
+                quote! { #iter.nth(#index) }
+            }
+        }
     }
 
-    let mut named_fields = false;
+    /// Expression for an `Option` of the index of the value behind the reference `self.id`
+    /// among the values of this field. Listed values are only compared when asked for with
+    /// `partial_eq`, as that needs the field type to implement `PartialEq`.
+    fn index(&self) -> TokenStream2 {
+        let id = &self.id;
+        match &self.source {
+            FieldSource::Variants => quote! { EveryVariant::variant_index(#id) },
+            FieldSource::EdgeCases => quote! { ::every_variant::EdgeCases::edge_case_index(#id) },
+            FieldSource::Values {
+                partial_eq: true, ..
+            } => {
+                let iter = self.iter();
+                quote! { #iter.position(|value| value == *#id) }
+            }
+            FieldSource::Values { .. } => quote! { ::core::option::Option::None },
+            FieldSource::Skip(_) => quote! { ::core::option::Option::Some(0usize) },
+        }
+    }
+
+    /// Expression for an `Option` of a value of this field picked at random using `rng`
+    fn random(&self) -> TokenStream2 {
+        match &self.source {
+            FieldSource::Variants => {
+                let ty = &self.ty;
+                quote! { <#ty as EveryVariant>::random_variant(rng) }
+            }
+            _ => {
+                let count = self.count();
+                let iter = self.iter();
+                quote! { #iter.nth(rng.below(#count)) }
+            }
+        }
+    }
+
+    /// Expression for a `Vec` of the values of this field that cover every combination of
+    /// `strength` values within it
+    fn covering(&self) -> TokenStream2 {
+        match &self.source {
+            FieldSource::Variants => {
+                let ty = &self.ty;
+                quote! { <#ty as EveryVariant>::every_t_wise(strength) }
+            }
+            _ => {
+                let iter = self.iter();
+                quote! { #iter.collect() }
+            }
+        }
+    }
+
+    /// Whether the enum variants of the field type are tracked by `variant_paths`, which leaves
+    /// out fields that do not take every variant of their type
+    fn is_tracked(&self) -> bool {
+        matches!(self.source, FieldSource::Variants)
+    }
 
-    let names: Vec<TokenStream2> = field_gen
+    /// Expression for a `Vec` of the values of this field that contain every enum variant in it
+    fn shallow(&self) -> TokenStream2 {
+        match &self.source {
+            FieldSource::Variants => {
+                let ty = &self.ty;
+                quote! { <#ty as EveryVariant>::every_variant_shallow() }
+            }
+            _ => {
+                let iter = self.iter();
+                quote! { #iter.collect() }
+            }
+        }
+    }
+
+    /// Expression for an iterator over the values of this field
+    fn iter(&self) -> TokenStream2 {
+        let ty = &self.ty;
+        match &self.source {
+            FieldSource::Skip(value) => quote! { ::core::iter::once::<#ty>(#value) },
+            FieldSource::Variants => quote! { <#ty as EveryVariant>::every_variant_iter() },
+            FieldSource::EdgeCases => quote! {
+                <#ty as ::every_variant::EdgeCases>::edge_cases().into_iter()
+            },
+            FieldSource::Values { values, .. } => {
+                let len = values.len();
+                let values = values.iter().map(|value| match value {
+                    // lets `"text"` be used for `String` fields as well
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(_), ..
+                    }) => quote! { ::core::convert::Into::<#ty>::into(#value) },
+                    _ => quote! { #value },
+                });
+                quote! {{
+                    let values: [#ty; #len] = [ #( #values ),* ];
+                    ::core::iter::IntoIterator::into_iter(values)
+                }}
+            }
+        }
+    }
+}
+
+/// Calls `option` for every `key` in the `#[every_variant(key, key = ...)]` attributes, with
+/// the input positioned right after the key
+fn parse_options<F>(attrs: &[Attribute], mut option: F)
+where
+    F: FnMut(&Ident, ParseStream) -> syn::Result<()>,
+{
+    for attr in attrs
         .iter()
-        .map(|field_gen| {
-            let field_id = &field_gen.id;
-            let field_name = &field_gen.name;
-            if let Some(field_name) = field_name {
-                named_fields = true;
-                quote! {
-                    #field_name: #field_id
+        .filter(|attr| attr.path.is_ident("every_variant"))
+    {
+        let parsed = attr.parse_args_with(|input: ParseStream| {
+            while !input.is_empty() {
+                let key: Ident = input.parse()?;
+                option(&key, input)?;
+
+                if !input.is_empty() {
+                    input.parse::<Comma>()?;
                 }
+            }
+            Ok(())
+        });
+
+        if let Err(err) = parsed {
+            abort!(err.span(), "{}", err);
+        }
+    }
+}
+
+fn unknown_option(key: &Ident) -> syn::Error {
+    syn::Error::new(key.span(), "Unknown every_variant option")
+}
+
+/// Parses the `#[every_variant(...)]` attributes of a field into where its values come from
+fn field_source(attrs: &[Attribute]) -> FieldSource {
+    let mut source = FieldSource::Variants;
+    let mut sourced_by: Option<Ident> = None;
+    let mut compared = None;
+
+    parse_options(attrs, |key, input| {
+        // each of these picks the values of the field on its own
+        if ["values", "edge_cases", "skip", "default"]
+            .iter()
+            .any(|option| key == option)
+        {
+            if let Some(previous) = &sourced_by {
+                return Err(syn::Error::new(
+                    key.span(),
+                    format!("`{}` can not be combined with `{}`", key, previous),
+                ));
+            }
+            sourced_by = Some(key.clone());
+        }
+
+        if key == "values" {
+            input.parse::<Token![=]>()?;
+            let content;
+            bracketed!(content in input);
+            let values = Punctuated::<Expr, Comma>::parse_terminated(&content)?;
+            source = FieldSource::Values {
+                values: values.into_iter().collect(),
+                partial_eq: false,
+            };
+        } else if key == "partial_eq" {
+            compared = Some(key.clone());
+        } else if key == "edge_cases" {
+            source = FieldSource::EdgeCases;
+        } else if key == "skip" {
+            source = FieldSource::Skip(Box::new(syn::parse_quote! {
+                ::core::default::Default::default()
+            }));
+        } else if key == "default" {
+            input.parse::<Token![=]>()?;
+            source = FieldSource::Skip(Box::new(input.parse()?));
+        } else {
+            return Err(unknown_option(key));
+        }
+        Ok(())
+    });
+
+    if let Some(key) = compared {
+        match &mut source {
+            FieldSource::Values { partial_eq, .. } => *partial_eq = true,
+            _ => abort!(key, "partial_eq only has an effect next to values"),
+        }
+    }
+
+    source
+}
+
+/// Whether an enum variant is marked with `#[every_variant(skip)]`
+fn variant_skipped(attrs: &[Attribute]) -> bool {
+    let mut skip = false;
+
+    parse_options(attrs, |key, _input| {
+        if key == "skip" {
+            skip = true;
+            Ok(())
+        } else {
+            Err(unknown_option(key))
+        }
+    });
+
+    skip
+}
+
+/// Parses the `#[every_variant(max_depth = ...)]` attribute of a struct or enum
+fn max_depth(attrs: &[Attribute]) -> Option<Expr> {
+    let mut depth = None;
+
+    parse_options(attrs, |key, input| {
+        if key == "max_depth" {
+            input.parse::<Token![=]>()?;
+            depth = Some(input.parse()?);
+            Ok(())
+        } else {
+            Err(unknown_option(key))
+        }
+    });
+
+    depth
+}
+
+fn field_gens<'a, I: IntoIterator<Item = &'a Field>>(fields: I) -> Vec<FieldGen> {
+    fields
+        .into_iter()
+        .enumerate()
+        .map(|(idx, field)| FieldGen {
+            id: Ident::new(&format!("v{}", &idx.to_string()), Span::call_site()),
+            ty: field.ty.clone(),
+            name: field.ident.clone(),
+            member: match &field.ident {
+                Some(name) => name.to_string(),
+                None => idx.to_string(),
+            },
+            source: field_source(&field.attrs),
+        })
+        .collect()
+}
+
+/// Builds the expression constructing the value of `variant`. `value` gives the expression
+/// for every field that is not skipped, from its position among those fields.
+fn do_constructor<F>(variant: &VariantGen, mut value: F) -> TokenStream2
+where
+    F: FnMut(usize, &FieldGen) -> TokenStream2,
+{
+    let mut position = 0;
+    let values: Vec<TokenStream2> = variant
+        .fields
+        .iter()
+        .map(|field| {
+            let value = if let FieldSource::Skip(value) = &field.source {
+                quote! { #value }
             } else {
-                quote! {
-                    #field_id
-                }
+                position += 1;
+                value(position - 1, field)
+            };
+            match &field.name {
+                Some(field_name) => quote! { #field_name: #value },
+                None => value,
             }
         })
         .collect();
 
-    let mut enum_gen = if !named_fields {
-        quote! {
-            let s = Self :: #var_id (
-              #( #names.clone() ),*
-            );
-            vec.push(s);
-        }
-    } else {
-        quote! {
-            let s = Self :: #var_id {
-              #( #names.clone() ),*
-            };
-            vec.push(s);
+    variant.delimit(values)
+}
+
+/// Generates an iterator expression that lazily walks the cartesian product of the fields,
+/// the first field changing slowest
+fn do_iter_gen(variant: &VariantGen) -> TokenStream2 {
+    let iterated: Vec<&FieldGen> = variant.iterated().collect();
+
+    // every field but the last is still needed by later iterations, so those are cloned
+    let constructor = do_constructor(variant, |position, field| {
+        let field_id = &field.id;
+        if position + 1 == iterated.len() {
+            quote! { #field_id }
+        } else {
+            quote! { #field_id.clone() }
         }
+    });
+    let (last, rest) = match iterated.split_last() {
+        Some(split) => split,
+        None => return quote! { std::iter::once(#constructor) },
     };
 
-    for field in field_gen.iter().rev() {
+    let last_id = &last.id;
+    let last_iter = last.iter();
+    let mut iter_gen = quote! {
+        #last_iter.map(move |#last_id| #constructor)
+    };
+
+    for (idx, field) in rest.iter().enumerate().rev() {
         let fname = &field.id;
-        let ftype = &field.ty;
+        let fiter = field.iter();
+        // the produced iterator has to own copies of the fields bound further out
+        let outer: Vec<&Ident> = rest[..idx].iter().map(|f| &f.id).collect();
 
-        enum_gen = quote! {
-            for #fname in <#ftype as EveryVariant>::every_variant() {
-                #enum_gen
-            }
+        iter_gen = quote! {
+            ::every_variant::product::product(#fiter, move |#fname| {
+                #( let #outer = #outer.clone(); )*
+                #iter_gen
+            })
         };
     }
 
-    let variant_gen = quote! {
-        #enum_gen
-    };
+    iter_gen
+}
 
-    variant_gen
+/// Generates an expression for the values in which every combination of values of any
+/// `strength` fields shows up, each field contributing its own combinations, together with
+/// their coverage
+fn do_covering_gen(variant: &VariantGen) -> TokenStream2 {
+    let iterated: Vec<&FieldGen> = variant.iterated().collect();
+    let ids = iterated.iter().map(|f| &f.id);
+    let types = iterated.iter().map(|f| &f.ty);
+    let lists = iterated.iter().map(|f| f.covering());
+    let lens = iterated.iter().map(|f| &f.id);
+
+    let constructor = do_constructor(variant, |position, field| {
+        let field_id = &field.id;
+        quote! { #field_id[row[#position]].clone() }
+    });
+
+    quote! {{
+        #( let #ids: std::vec::Vec<#types> = #lists; )*
+        let sizes = [ #( #lens.len() ),* ];
+        let rows = ::every_variant::covering::covering_array(&sizes, strength);
+        let coverage = ::every_variant::covering::coverage(&sizes, strength, &rows);
+        (rows.into_iter().map(move |row| #constructor), coverage)
+    }}
+}
+
+/// Generates an iterator expression for values of the variant with every value of each field,
+/// the other fields keeping their first value
+fn do_shallow_gen(variant: &VariantGen) -> TokenStream2 {
+    let iterated: Vec<&FieldGen> = variant.iterated().collect();
+    let ids = iterated.iter().map(|f| &f.id);
+    let types = iterated.iter().map(|f| &f.ty);
+    let lists = iterated.iter().map(|f| f.shallow());
+    let lens = iterated.iter().map(|f| &f.id);
+
+    let constructor = do_constructor(variant, |position, field| {
+        let field_id = &field.id;
+        quote! { #field_id[row[#position]].clone() }
+    });
+
+    quote! {{
+        #( let #ids: std::vec::Vec<#types> = #lists; )*
+        let sizes = [ #( #lens.len() ),* ];
+        ::every_variant::covering::one_at_a_time(&sizes)
+            .into_iter()
+            .map(move |row| #constructor)
+    }}
 }
 
 fn do_bound_gen(generics: &Generics) -> Generics {
@@ -114,186 +450,577 @@ fn do_bound_gen(generics: &Generics) -> Generics {
     let sized_bound = make_bound("Sized");
 
     for param in &mut generics.params {
-        match param {
-            GenericParam::Type(some_type) => {
-                some_type.bounds.push(everyvariant_bound.clone());
-                some_type.bounds.push(clone_bound.clone());
-                some_type.bounds.push(sized_bound.clone());
-            }
-            _ => {}
+        if let GenericParam::Type(some_type) = param {
+            some_type.bounds.push(everyvariant_bound.clone());
+            some_type.bounds.push(clone_bound.clone());
+            some_type.bounds.push(sized_bound.clone());
         }
     }
     generics
 }
 
-#[proc_macro_error]
-#[proc_macro_derive(EveryVariant)]
-pub fn derive_every_variant(item: TokenStream) -> TokenStream {
-    let item: syn::Item = syn::parse(item).expect("Failed to parse input item");
+/// How the fields of a struct or enum variant are written, also when there are none of them
+#[derive(Debug, Clone, Copy)]
+enum Shape {
+    Unit,
+    Named,
+    Unnamed,
+}
 
-    match item {
-        Item::Enum(ref it) => {
-            //println!("Enum: {}", it.ident);
-            let _attrs = &it.attrs;
+/// A struct, or one of the variants of an enum, that values are generated for
+struct VariantGen {
+    /// `Self` or `Self::Variant`
+    path: TokenStream2,
+    /// `Enum::Variant` in the paths of `variant_paths`, structs have none
+    label: Option<String>,
+    shape: Shape,
+    fields: Vec<FieldGen>,
+}
 
-            let name = &it.ident;
+impl VariantGen {
+    fn new(path: TokenStream2, label: Option<String>, fields: &Fields) -> Self {
+        let shape = match fields {
+            Fields::Unit => Shape::Unit,
+            Fields::Named(_) => Shape::Named,
+            Fields::Unnamed(_) => Shape::Unnamed,
+        };
+        VariantGen {
+            path,
+            label,
+            shape,
+            fields: field_gens(fields),
+        }
+    }
 
-            let variants = &it.variants;
+    /// The path followed by `items` in the braces or parentheses of this variant, such as the
+    /// values of the fields
+    fn delimit(&self, items: Vec<TokenStream2>) -> TokenStream2 {
+        let path = &self.path;
+        match self.shape {
+            Shape::Unit => quote! { #path },
+            Shape::Named => quote! { #path { #( #items ),* } },
+            Shape::Unnamed => quote! { #path ( #( #items ),* ) },
+        }
+    }
 
-            let mut variant_generators = Vec::new();
-            for var in variants {
-                let varid = &var.ident;
+    fn iterated(&self) -> impl Iterator<Item = &FieldGen> {
+        self.fields.iter().filter(|f| !f.is_skipped())
+    }
 
-                match var.fields {
-                    syn::Fields::Unnamed(ref fields) => {
-                        let variant_gen = do_enum_gen(&varid, &fields.unnamed);
-                        variant_generators.push(variant_gen);
-                        //println!("quote: {:?}", layeridarm.to_string());
-                    }
-                    syn::Fields::Unit => {
-                        // Generate a parsing arm for unit structs
-                        let variant_gen = quote! {
-                            vec.push(Self::#varid);
-                        };
-                        variant_generators.push(variant_gen);
-                    }
-                    syn::Fields::Named(ref fields) => {
-                        let variant_gen = do_enum_gen(&varid, &fields.named);
-                        variant_generators.push(variant_gen);
-                    }
+    /// Expression for the number of values of this variant, the product of its fields
+    fn count(&self) -> TokenStream2 {
+        let counts = self.iterated().map(FieldGen::count);
+        quote! { 1usize #( .saturating_mul(#counts) )* }
+    }
+
+    /// Statements returning the value at `index`, which has to be less than the count of this
+    /// variant. Like a mixed radix number, the last field is the least significant digit.
+    fn nth(&self) -> TokenStream2 {
+        let fields: Vec<&FieldGen> = self.iterated().collect();
+        let ids = fields.iter().rev().map(|f| &f.id);
+        let counts = fields.iter().rev().map(|f| f.count());
+        let field_index = format_ident!("field_index");
+        let nths = fields.iter().rev().map(|f| f.nth(&field_index));
+        let constructor = do_constructor(self, |_, field| {
+            let field_id = &field.id;
+            quote! { #field_id }
+        });
+
+        quote! {
+            #(
+                let count = #counts;
+                let #field_index = index % count;
+                index /= count;
+                let #ids = #nths?;
+            )*
+            let _ = index;
+            return ::core::option::Option::Some(#constructor);
+        }
+    }
+
+    /// Pattern matching this variant, binding references to the fields that are not skipped
+    fn pattern(&self) -> TokenStream2 {
+        let bindings: Vec<TokenStream2> = self
+            .fields
+            .iter()
+            .map(|field| {
+                let binding = if field.is_skipped() {
+                    quote! { _ }
+                } else {
+                    let id = &field.id;
+                    quote! { #id }
+                };
+                match &field.name {
+                    Some(name) => quote! { #name: #binding },
+                    None => binding,
                 }
+            })
+            .collect();
+
+        self.delimit(bindings)
+    }
+
+    /// Expression for an `Option` of the index of the fields bound by `pattern` within this
+    /// variant, the first field being the most significant digit
+    fn index(&self) -> TokenStream2 {
+        let counts = self.iterated().map(FieldGen::count);
+        let indexes = self.iterated().map(FieldGen::index);
+
+        quote! {{
+            let index = ::core::option::Option::Some(0usize);
+            #(
+                let index = index?.checked_mul(#counts)?.checked_add(#indexes?);
+            )*
+            index
+        }}
+    }
+
+    /// Statements appending the path of this variant to `paths` when it is an enum variant,
+    /// followed by the paths within its fields. With `observed`, only the paths of the values
+    /// bound by `pattern` are appended.
+    fn paths(&self, observed: bool) -> TokenStream2 {
+        let (own, prefix) = match &self.label {
+            Some(label) => (
+                quote! { paths.push(format!("{}{}", prefix, #label)); },
+                quote! { format!("{}{}.", prefix, #label) },
+            ),
+            None => (quote! {}, quote! { prefix.to_string() }),
+        };
+        let fields = self.fields.iter().filter(|f| f.is_tracked()).map(|field| {
+            let member = &field.member;
+            let field_prefix = quote! { &format!("{}{}.", variant_prefix, #member) };
+            if observed {
+                let id = &field.id;
+                quote! { EveryVariant::observed_variant_paths(#id, #field_prefix, paths); }
+            } else {
+                let ty = &field.ty;
+                quote! { <#ty as EveryVariant>::variant_paths(#field_prefix, paths); }
+            }
+        });
+
+        quote! {
+            #own
+            let variant_prefix = #prefix;
+            #( #fields )*
+            let _ = variant_prefix;
+        }
+    }
+
+    /// Statements returning this variant with every field picked at random, or `None` when
+    /// one of them has no values
+    fn random(&self) -> TokenStream2 {
+        let ids = self.iterated().map(|f| &f.id);
+        let randoms = self.iterated().map(FieldGen::random);
+        let constructor = do_constructor(self, |_, field| {
+            let field_id = &field.id;
+            quote! { #field_id }
+        });
+
+        quote! {
+            #( let #ids = #randoms?; )*
+            return ::core::option::Option::Some(#constructor);
+        }
+    }
+}
+
+fn do_impl_gen(
+    name: &Ident,
+    generics: &Generics,
+    attrs: &[Attribute],
+    variants: &[VariantGen],
+) -> TokenStream2 {
+    let iter_gens = variants.iter().map(do_iter_gen);
+    let counts = variants.iter().map(VariantGen::count);
+    let covering_gens = variants.iter().map(do_covering_gen);
+    let shallow_gens = variants.iter().map(do_shallow_gen);
+    let nth_gens = variants.iter().map(VariantGen::nth);
+    let nth_counts = variants.iter().map(VariantGen::count);
+    let patterns = variants.iter().map(VariantGen::pattern);
+    let indexes = variants.iter().map(VariantGen::index);
+    let offsets = (0..variants.len()).map(|idx| {
+        let earlier = variants[..idx].iter().map(VariantGen::count);
+        quote! { 0usize #( .saturating_add(#earlier) )* }
+    });
+    let path_gens = variants.iter().map(|v| v.paths(false));
+    let path_counts = variants.iter().map(VariantGen::count);
+    let observed_patterns = variants.iter().map(VariantGen::pattern);
+    let observed_gens = variants.iter().map(|v| v.paths(true));
+    let random_gens = variants.iter().map(VariantGen::random);
+    let random_counts = variants.iter().map(VariantGen::count);
+    let variant_idx = 0..variants.len();
+    let variant_len = variants.len();
+
+    let max_depth_gen = max_depth(attrs).map(|depth| {
+        quote! {
+            fn max_depth() -> usize {
+                #depth
             }
+        }
+    });
 
-            let bounded_generics = do_bound_gen(&it.generics);
-            let (impl_generics, ty_generics, where_clause) = bounded_generics.split_for_impl();
+    let bounded_generics = do_bound_gen(generics);
+    let (impl_generics, ty_generics, where_clause) = bounded_generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics EveryVariant for #name #ty_generics #where_clause {
+            fn every_variant() -> std::vec::Vec<Self> {
+                Self::every_variant_iter().collect()
+            }
+
+            fn every_variant_iter() -> impl std::iter::Iterator<Item = Self> {
+                std::iter::empty()
+                #( .chain(#iter_gens) )*
+            }
+
+            fn variant_count() -> usize {
+                0usize #( .saturating_add(#counts) )*
+            }
 
-            let out = quote! {
-                impl #impl_generics EveryVariant for #name #ty_generics #where_clause {
-                    fn every_variant() -> std::vec::Vec<Self> {
-                        let mut vec = std::vec::Vec::new();
-                        #( #variant_generators )*
-                        vec
+            fn covering_variants(
+                strength: usize,
+            ) -> (std::vec::Vec<Self>, ::every_variant::covering::Coverage) {
+                let mut vec = std::vec::Vec::new();
+                let mut coverage = ::every_variant::covering::Coverage::complete(strength, 0);
+                #(
+                    let (values, variant_coverage) = #covering_gens;
+                    vec.extend(values);
+                    coverage = coverage + variant_coverage;
+                )*
+                coverage.full_product = Self::variant_count();
+                (vec, coverage)
+            }
+
+            #max_depth_gen
+
+            fn every_variant_shallow() -> std::vec::Vec<Self> {
+                let mut vec = std::vec::Vec::new();
+                #( vec.extend(#shallow_gens); )*
+                vec
+            }
+
+            fn nth_variant(index: usize) -> ::core::option::Option<Self> {
+                let mut index = index;
+                #(
+                    let count = #nth_counts;
+                    if index < count {
+                        #nth_gens
                     }
+                    index -= count;
+                )*
+                let _ = index;
+                ::core::option::Option::None
+            }
 
-                    fn for_every_variant<F: Fn(&Self)>(closure: F) {
-                        let v = Self::every_variant();
+            #[allow(unreachable_code, unreachable_patterns)]
+            fn variant_index(&self) -> ::core::option::Option<usize> {
+                match self {
+                    #( #patterns => #indexes?.checked_add(#offsets), )*
+                    // skipped variants
+                    _ => ::core::option::Option::None,
+                }
+            }
 
-                        for elem in &v {
-                            closure(&elem);
-                        }
+            fn variant_paths(prefix: &str, paths: &mut std::vec::Vec<std::string::String>) {
+                // variants without values, also when nested too deep, are left out
+                #(
+                    if #path_counts != 0 {
+                        #path_gens
                     }
+                )*
+                let _ = (prefix, paths);
+            }
 
+            #[allow(unused_variables, unreachable_patterns)]
+            fn observed_variant_paths(
+                &self,
+                prefix: &str,
+                paths: &mut std::vec::Vec<std::string::String>,
+            ) {
+                match self {
+                    #( #observed_patterns => { #observed_gens } )*
+                    // skipped variants
+                    _ => {}
                 }
-            };
+                let _ = (prefix, paths);
+            }
+
+            fn random_variant(rng: &mut ::every_variant::Rng) -> ::core::option::Option<Self> {
+                // every variant that has values is equally likely
+                let available: [bool; #variant_len] = [ #( #random_counts != 0 ),* ];
+                let mut pick = rng.below(available.iter().filter(|a| **a).count());
+                #(
+                    if available[#variant_idx] {
+                        if pick == 0 {
+                            #random_gens
+                        }
+                        pick -= 1;
+                    }
+                )*
+                let _ = pick;
+                ::core::option::Option::None
+            }
+        }
+    }
+}
+
+#[proc_macro_error]
+#[proc_macro_derive(EveryVariant, attributes(every_variant))]
+pub fn derive_every_variant(item: TokenStream) -> TokenStream {
+    let item: syn::Item = syn::parse(item).expect("Failed to parse input item");
+
+    match item {
+        Item::Enum(ref it) => {
+            let variants: Vec<VariantGen> = it
+                .variants
+                .iter()
+                .filter(|var| !variant_skipped(&var.attrs))
+                .map(|var| {
+                    let varid = &var.ident;
+                    VariantGen::new(
+                        quote! { Self::#varid },
+                        Some(format!("{}::{}", it.ident, varid)),
+                        &var.fields,
+                    )
+                })
+                .collect();
+
+            let out = do_impl_gen(&it.ident, &it.generics, &it.attrs, &variants);
 
             // println!("{}", out);
             out.into()
         }
         Item::Struct(ref it) => {
-            // println!("struct: {:#?}", it);
-
-            let name = &it.ident;
-
-            //let mut member_generator = Vec::new();
-
-            // This is used, but the compiler does not seem to detect it?
-            #[allow(unused_assignments)]
-            let mut structgen = quote! {};
-
-            if it.fields.iter().any(|f| f.ident.is_none()) {
-                let mut fieldgens = Vec::new();
-                // Here we come if its an struct with anonymous fields
-                for (idx, field) in it.fields.iter().enumerate() {
-                    if let syn::Type::Path(path) = field.ty.clone() {
-                        fieldgens.push(AnonStructFieldGen {
-                            id: Ident::new(&format!("v{}", &idx.to_string()), Span::call_site()),
-                            ty: path.clone(),
-                        });
-                    } else {
-                        abort!(field, "Ident is missing");
-                    }
-                }
+            let variant = VariantGen::new(quote! { Self }, None, &it.fields);
 
-                let names: Vec<Ident> = fieldgens.iter().map(|f| f.id.clone()).collect();
-                structgen = quote! {
-                    let s = #name(
-                      #( #names.clone() ),*
-                    );
-                    vec.push(s);
-                };
+            let out = do_impl_gen(&it.ident, &it.generics, &it.attrs, &[variant]);
 
-                for field in fieldgens.iter().rev() {
-                    let fname = &field.id;
-                    let ftype = &field.ty;
+            // println!("{}", out);
+            out.into()
+        }
+        _ => {
+            abort!(item, "Only has an effect on enums and structs");
+        }
+    }
+}
 
-                    structgen = quote! {
-                        for #fname in <#ftype as EveryVariant>::every_variant() {
-                            #structgen
-                        }
-                    };
+/// The owned type a crashtest has to generate for an argument, and how it is passed on
+fn crashtest_arg(ty: &Type) -> (Type, TokenStream2) {
+    match ty {
+        // `&'static str` and friends have implementations of their own
+        Type::Reference(reference) if !matches!(&reference.lifetime, Some(lifetime) if lifetime.ident == "static") =>
+        {
+            let owned: Type = match &*reference.elem {
+                Type::Path(path) if path.path.is_ident("str") => syn::parse_quote! { String },
+                Type::Slice(slice) => {
+                    let elem = &slice.elem;
+                    syn::parse_quote! { std::vec::Vec<#elem> }
                 }
+                elem => elem.clone(),
+            };
+            let pass = if reference.mutability.is_some() {
+                quote! { &mut }
             } else {
-                let mut fieldgens = Vec::new();
-                for field in it.fields.iter() {
-                    if let Some(name) = field.ident.clone() {
-                        if let syn::Type::Path(path) = field.ty.clone() {
-                            let fieldgen = StructFieldGen { name, ty: path };
-                            fieldgens.push(fieldgen);
-                        } else {
-                            abort!(field, "Ident is missing");
-                        }
-                    }
-                }
+                quote! { & }
+            };
+            (owned, pass)
+        }
+        _ => (ty.clone(), quote! {}),
+    }
+}
 
-                let names: Vec<Ident> = fieldgens.iter().map(|f| f.name.clone()).collect();
-                structgen = quote! {
-                    let s = Self {
-                      #( #names: #names.clone() ),*
-                    };
-                    vec.push(s);
-                };
+/// Whether a crashtest argument is marked with `#[every_variant(edge_cases)]`
+fn arg_edge_cases(attrs: &[Attribute]) -> bool {
+    let mut edge_cases = false;
 
-                for field in fieldgens.iter().rev() {
-                    let fname = &field.name;
-                    let ftype = &field.ty;
-                    //println!("type: {}, dbg: {:?}", ftype.to_token_stream(), ftype);
+    parse_options(attrs, |key, _input| {
+        if key == "edge_cases" {
+            edge_cases = true;
+            Ok(())
+        } else {
+            Err(unknown_option(key))
+        }
+    });
 
-                    structgen = quote! {
-                        for #fname in <#ftype as EveryVariant>::every_variant() {
-                            #structgen
-                        }
-                    };
-                }
-            }
+    edge_cases
+}
 
-            let bounded_generics = do_bound_gen(&it.generics);
-            let (impl_generics, ty_generics, where_clause) = bounded_generics.split_for_impl();
+/// Creates a `crashtest_<name>()` function next to the annotated function, that calls it with
+/// every combination of `EveryVariant` arguments. Reference arguments are built from owned
+/// variants, and a call that panics panics again with its arguments added to the message.
+/// Arguments marked with `#[every_variant(edge_cases)]` take their `EdgeCases` instead.
+#[proc_macro_error]
+#[proc_macro_attribute]
+pub fn crashtest_every_variant(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut func: ItemFn = syn::parse(item).expect("Failed to parse input function");
+    let mut arg_sources = Vec::new();
+    for input in func.sig.inputs.iter_mut() {
+        if let FnArg::Typed(arg) = input {
+            arg_sources.push(arg_edge_cases(&arg.attrs));
+            // the attribute is only known to this macro, so it can not be left on the function
+            arg.attrs
+                .retain(|attr| !attr.path.is_ident("every_variant"));
+        }
+    }
+    let sig = &func.sig;
 
-            let out = quote! {
-                impl #impl_generics EveryVariant for #name #ty_generics #where_clause {
-                    fn every_variant() -> std::vec::Vec<Self> {
-                        let mut vec = std::vec::Vec::new();
-                        #structgen
-                        vec
-                    }
+    if sig.asyncness.is_some() {
+        abort!(sig.asyncness, "Async functions can not be crashtested");
+    }
+    if let Some(param) = sig.generics.type_params().next() {
+        abort!(param, "Generic functions can not be crashtested");
+    }
 
-                    fn for_every_variant<F: Fn(&Self)>(closure: F) {
-                        let v = Self::every_variant();
+    let name = &sig.ident;
+    let test_name = format_ident!("crashtest_{}", name);
+    let vis = &func.vis;
 
-                        for elem in &v {
-                            closure(&elem);
-                        }
-                    }
-                }
-            };
+    let mut arg_ids = Vec::new();
+    let mut arg_names = Vec::new();
+    let mut arg_values = Vec::new();
+    let mut arg_iters = Vec::new();
+    for (idx, input) in sig.inputs.iter().enumerate() {
+        let arg = match input {
+            FnArg::Typed(arg) => arg,
+            FnArg::Receiver(receiver) => abort!(receiver, "Methods can not be crashtested"),
+        };
+        let id = format_ident!("arg{}", idx);
+        let (owned, pass) = crashtest_arg(&arg.ty);
 
-            // println!("{}", out);
-            out.into()
+        arg_names.push(match &*arg.pat {
+            Pat::Ident(pat) => pat.ident.to_string(),
+            _ => id.to_string(),
+        });
+        arg_values.push(quote! { #pass #id.clone() });
+        arg_iters.push(if arg_sources[idx] {
+            quote! { <#owned as ::every_variant::EdgeCases>::edge_cases() }
+        } else {
+            quote! { <#owned as EveryVariant>::every_variant_iter() }
+        });
+        arg_ids.push(id);
+    }
+
+    let format = format!(
+        "{} panicked with arguments: {}: {{}}",
+        name,
+        arg_names
+            .iter()
+            .map(|name| format!("{} = {{:?}}", name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    let mut call = quote! {
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _ = #name( #( #arg_values ),* );
+        }));
+        if let Err(panic) = result {
+            let message = ::every_variant::outcome::panic_message(&*panic);
+            panic!(#format, #( #arg_ids, )* message);
         }
-        _ => {
-            abort!(item, "Only has an effect on enums and structs");
+    };
+
+    for (id, iter) in arg_ids.iter().zip(&arg_iters).rev() {
+        call = quote! {
+            for #id in #iter {
+                #call
+            }
+        };
+    }
+
+    let doc = format!(
+        "Calls `{}` with every combination of variants of its arguments",
+        name
+    );
+
+    let out = quote! {
+        #func
+
+        #[doc = #doc]
+        #[allow(dead_code)]
+        #vis fn #test_name() {
+            #call
         }
+    };
+
+    out.into()
+}
+
+fn do_crashtests(input: TokenStream) -> TokenStream {
+    let functions = match Punctuated::<Path, Comma>::parse_terminated.parse(input) {
+        Ok(functions) => functions,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    let mut tests = Vec::new();
+    for function in functions {
+        let mut crashtest = function.clone();
+        let last = match crashtest.segments.last_mut() {
+            Some(last) => last,
+            None => abort!(function, "Expected a path to a function"),
+        };
+        last.ident = format_ident!("crashtest_{}", last.ident);
+
+        // the tests live in a module of their own, and would shadow a function of the same name
+        // reached through the glob import, so those are called through `super`. Longer paths
+        // start with a module, found through the glob import, or with the name of a crate.
+        let first = crashtest.segments.first().map(|s| s.ident.to_string());
+        let crashtest = match first.as_deref() {
+            Some("self") => {
+                let rest = crashtest.segments.iter().skip(1);
+                quote! { super #( :: #rest )* }
+            }
+            Some("super") => quote! { super :: #crashtest },
+            _ if crashtest.leading_colon.is_none() && crashtest.segments.len() == 1 => {
+                quote! { super :: #crashtest }
+            }
+            _ => quote! { #crashtest },
+        };
+
+        // functions from different modules should not end up with the same test name
+        let name = function
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .filter(|segment| !matches!(segment.as_str(), "self" | "super" | "crate"))
+            .collect::<Vec<_>>()
+            .join("_");
+        let test_name = format_ident!("crashtest_{}", name);
+
+        tests.push(quote! {
+            #[test]
+            fn #test_name() {
+                #crashtest();
+            }
+        });
     }
+
+    let out = quote! {
+        mod crashtests {
+            #[allow(unused_imports)]
+            use super::*;
+
+            #( #tests )*
+        }
+    };
+
+    out.into()
+}
+
+/// Turns a list of functions annotated with `#[crashtest_every_variant]` into `#[test]`
+/// functions, each calling the generated `crashtest_<name>()`. Functions in other modules or
+/// crates can be listed by path. The tests are put in a `crashtests` module, so the macro can
+/// be used once per module.
+#[proc_macro_error]
+#[proc_macro]
+pub fn crashtests(input: TokenStream) -> TokenStream {
+    do_crashtests(input)
+}
+
+/// Same as `crashtests!`
+#[proc_macro_error]
+#[proc_macro]
+pub fn craschtests(input: TokenStream) -> TokenStream {
+    do_crashtests(input)
 }
 
 #[cfg(test)]
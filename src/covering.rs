@@ -0,0 +1,442 @@
+//! Covering arrays: small sets of rows in which every combination of values of any `strength`
+//! columns shows up at least once.
+//!
+//! Rows are built greedily: each row starts from a combination that is not covered yet, and
+//! the remaining columns get the value that covers the most new combinations.
+
+use std::fmt;
+use std::ops::Add;
+
+/// How well a set of rows covers the combinations of values of any `strength` columns, and
+/// how it compares to the full product of the columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Coverage {
+    /// The number of columns combined, 2 for pairwise
+    pub strength: usize,
+    /// Combinations of values that show up in at least one row
+    pub covered: usize,
+    /// Combinations of values there are
+    pub combinations: usize,
+    /// The number of rows
+    pub rows: usize,
+    /// The number of rows of the full product, saturating at `usize::MAX`
+    pub full_product: usize,
+}
+
+impl Coverage {
+    /// Coverage of the full product of a single column with `rows` values
+    pub fn complete(strength: usize, rows: usize) -> Self {
+        Coverage {
+            strength,
+            covered: rows,
+            combinations: rows,
+            rows,
+            full_product: rows,
+        }
+    }
+
+    /// The share of combinations covered, between 0 and 1
+    pub fn ratio(&self) -> f64 {
+        if self.combinations == 0 {
+            1.0
+        } else {
+            self.covered as f64 / self.combinations as f64
+        }
+    }
+
+    /// The size of the set compared to the full product, between 0 and 1
+    pub fn size_ratio(&self) -> f64 {
+        if self.full_product == 0 {
+            1.0
+        } else {
+            self.rows as f64 / self.full_product as f64
+        }
+    }
+}
+
+/// Adds up the coverage of sets that are concatenated, like the variants of an enum
+impl Add for Coverage {
+    type Output = Coverage;
+
+    fn add(self, other: Coverage) -> Coverage {
+        Coverage {
+            strength: self.strength.max(other.strength),
+            covered: self.covered.saturating_add(other.covered),
+            combinations: self.combinations.saturating_add(other.combinations),
+            rows: self.rows.saturating_add(other.rows),
+            full_product: self.full_product.saturating_add(other.full_product),
+        }
+    }
+}
+
+impl fmt::Display for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}-wise coverage {:.1}% ({} of {} combinations) with {} of {} variants ({:.2}%)",
+            self.strength,
+            self.ratio() * 100.0,
+            self.covered,
+            self.combinations,
+            self.rows,
+            self.full_product,
+            self.size_ratio() * 100.0
+        )
+    }
+}
+
+/// Rows of indexes, one per column, such that every combination of values of any `strength`
+/// columns shows up in at least one row. `sizes` holds the number of values of each column.
+pub fn covering_array(sizes: &[usize], strength: usize) -> Vec<Vec<usize>> {
+    if sizes.contains(&0) {
+        return Vec::new();
+    }
+
+    let mut tuples = Tuples::new(sizes, strength);
+    let mut rows = Vec::new();
+
+    while let Some((combo, values)) = tuples.first_uncovered() {
+        let mut seed: Vec<Option<usize>> = vec![None; sizes.len()];
+        for (&column, &value) in combo.iter().zip(&values) {
+            seed[column] = Some(value);
+        }
+
+        // filling in the columns in a different order gives different rows, keep the best one
+        let row = (0..sizes.len().max(1))
+            .map(|start| tuples.fill(seed.clone(), start))
+            .max_by_key(|row| (tuples.newly_covered_by(row), std::cmp::Reverse(row.clone())))
+            .unwrap_or_default();
+
+        tuples.cover(&row);
+        rows.push(row);
+    }
+
+    rows
+}
+
+/// Same as [`covering_array`] with a strength of 2
+pub fn pairwise(sizes: &[usize]) -> Vec<Vec<usize>> {
+    covering_array(sizes, 2)
+}
+
+/// Rows in which every value of every column shows up at least once: the first values of all
+/// columns, followed by a row for each other value of a column, the other columns keeping their
+/// first value. Grows with the sum of the sizes instead of their product.
+pub fn one_at_a_time(sizes: &[usize]) -> Vec<Vec<usize>> {
+    if sizes.contains(&0) {
+        return Vec::new();
+    }
+
+    let mut rows = vec![vec![0; sizes.len()]];
+    for (column, &size) in sizes.iter().enumerate() {
+        for value in 1..size {
+            let mut row = vec![0; sizes.len()];
+            row[column] = value;
+            rows.push(row);
+        }
+    }
+    rows
+}
+
+/// Measures how well `rows` cover the combinations of values of any `strength` columns
+pub fn coverage(sizes: &[usize], strength: usize, rows: &[Vec<usize>]) -> Coverage {
+    let full_product = sizes
+        .iter()
+        .fold(1usize, |product, &size| product.saturating_mul(size));
+
+    let mut tuples = Tuples::new(sizes, strength);
+    for row in rows {
+        tuples.cover(row);
+    }
+
+    Coverage {
+        strength,
+        covered: tuples.covered.iter().flatten().filter(|&&c| c).count(),
+        combinations: tuples.covered.iter().map(Vec::len).sum(),
+        rows: rows.len(),
+        full_product,
+    }
+}
+
+/// The combinations of `strength` columns, and which of their values are covered
+struct Tuples<'a> {
+    sizes: &'a [usize],
+    combos: Vec<Vec<usize>>,
+    covered: Vec<Vec<bool>>,
+    /// How many uncovered combinations contain each value of each column, kept up to date by
+    /// [`Tuples::cover`]
+    uncovered: Vec<Vec<usize>>,
+}
+
+impl<'a> Tuples<'a> {
+    fn new(sizes: &'a [usize], strength: usize) -> Self {
+        // with fewer columns than the strength, all of them are combined
+        let combos = combinations(sizes.len(), strength.min(sizes.len()));
+        let covered = combos
+            .iter()
+            .map(|combo| vec![false; combo.iter().map(|&c| sizes[c]).product()])
+            .collect::<Vec<Vec<bool>>>();
+
+        let mut uncovered: Vec<Vec<usize>> = sizes.iter().map(|&size| vec![0; size]).collect();
+        for (combo, covered) in combos.iter().zip(&covered) {
+            for &column in combo {
+                for count in &mut uncovered[column] {
+                    *count += covered.len() / sizes[column];
+                }
+            }
+        }
+
+        Tuples {
+            sizes,
+            combos,
+            covered,
+            uncovered,
+        }
+    }
+
+    /// Index of the values of the columns in `combo`, mixed radix with the first column as the
+    /// most significant digit
+    fn index(&self, combo: &[usize], row: &[Option<usize>]) -> Option<usize> {
+        combo.iter().try_fold(0, |index, &column| {
+            row[column].map(|value| index * self.sizes[column] + value)
+        })
+    }
+
+    fn first_uncovered(&self) -> Option<(&[usize], Vec<usize>)> {
+        self.combos
+            .iter()
+            .zip(&self.covered)
+            .find_map(|(combo, covered)| {
+                let mut index = covered.iter().position(|covered| !covered)?;
+                let mut values = vec![0; combo.len()];
+                for (value, &column) in values.iter_mut().zip(combo).rev() {
+                    *value = index % self.sizes[column];
+                    index /= self.sizes[column];
+                }
+                Some((combo.as_slice(), values))
+            })
+    }
+
+    /// Fills in the empty columns of `row`, starting at `start` and wrapping around, each with
+    /// the value that covers the most new combinations
+    fn fill(&self, mut row: Vec<Option<usize>>, start: usize) -> Vec<usize> {
+        let columns = row.len();
+        for column in (start..columns).chain(0..start) {
+            if row[column].is_some() {
+                continue;
+            }
+
+            // ties are broken by how many combinations the value could still cover later on
+            let mut best = (0, (0, 0));
+            for value in 0..self.sizes[column] {
+                row[column] = Some(value);
+                let score = (
+                    self.newly_covered(&row, column),
+                    self.uncovered[column][value],
+                );
+                if score > best.1 {
+                    best = (value, score);
+                }
+            }
+            row[column] = Some(best.0);
+        }
+
+        row.into_iter().map(|value| value.unwrap_or(0)).collect()
+    }
+
+    /// How many uncovered combinations a complete row covers
+    fn newly_covered_by(&self, row: &[usize]) -> usize {
+        let row: Vec<Option<usize>> = row.iter().copied().map(Some).collect();
+        self.combos
+            .iter()
+            .zip(&self.covered)
+            .filter_map(|(combo, covered)| self.index(combo, &row).map(|index| !covered[index]))
+            .filter(|&new| new)
+            .count()
+    }
+
+    /// How many uncovered combinations containing `column` the filled in part of `row` covers
+    fn newly_covered(&self, row: &[Option<usize>], column: usize) -> usize {
+        self.combos
+            .iter()
+            .zip(&self.covered)
+            .filter(|(combo, _)| combo.contains(&column))
+            .filter_map(|(combo, covered)| self.index(combo, row).map(|index| !covered[index]))
+            .filter(|&new| new)
+            .count()
+    }
+
+    fn cover(&mut self, row: &[usize]) {
+        let row: Vec<Option<usize>> = row.iter().copied().map(Some).collect();
+        for idx in 0..self.combos.len() {
+            if let Some(index) = self.index(&self.combos[idx], &row) {
+                if !self.covered[idx][index] {
+                    self.covered[idx][index] = true;
+                    for &column in &self.combos[idx] {
+                        self.uncovered[column][row[column].unwrap_or(0)] -= 1;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Every way to pick `k` of `n` columns, in lexicographic order
+fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    let mut combos = Vec::new();
+    let mut combo: Vec<usize> = (0..k).collect();
+
+    if k > n {
+        return combos;
+    }
+
+    loop {
+        combos.push(combo.clone());
+
+        // find the rightmost column that can still move right
+        let mut pos = k;
+        loop {
+            if pos == 0 {
+                return combos;
+            }
+            pos -= 1;
+            if combo[pos] < n - k + pos {
+                break;
+            }
+        }
+
+        combo[pos] += 1;
+        for next in pos + 1..k {
+            combo[next] = combo[next - 1] + 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_pairwise(sizes: &[usize], rows: &[Vec<usize>]) {
+        for a in 0..sizes.len() {
+            for b in a + 1..sizes.len() {
+                for va in 0..sizes[a] {
+                    for vb in 0..sizes[b] {
+                        assert!(
+                            rows.iter().any(|row| row[a] == va && row[b] == vb),
+                            "columns {} and {} miss ({}, {})",
+                            a,
+                            b,
+                            va,
+                            vb
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn covers_every_pair() {
+        for sizes in [
+            vec![3, 3, 3, 3],
+            vec![2, 5, 1, 3, 4],
+            vec![4, 4, 4, 4, 4, 4, 4, 4],
+            vec![7],
+            vec![],
+        ] {
+            let rows = pairwise(&sizes);
+            assert_pairwise(&sizes, &rows);
+        }
+    }
+
+    #[test]
+    fn covers_every_triple() {
+        for sizes in [vec![2; 6], vec![3, 2, 4, 2, 3], vec![2, 3]] {
+            let rows = covering_array(&sizes, 3);
+            let report = coverage(&sizes, 3, &rows);
+            assert_eq!(report.covered, report.combinations, "{:?}", sizes);
+            assert!(report.rows < report.full_product || sizes.len() <= 3);
+
+            for (a, b, c) in [(0, 1, 2), (0, 2, 3), (1, 3, 4), (2, 3, 5)] {
+                if c >= sizes.len() {
+                    continue;
+                }
+                for va in 0..sizes[a] {
+                    for vb in 0..sizes[b] {
+                        for vc in 0..sizes[c] {
+                            assert!(rows
+                                .iter()
+                                .any(|row| row[a] == va && row[b] == vb && row[c] == vc));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn medium_strength_three_in_bounded_time() {
+        let sizes = [10; 6];
+        let start = std::time::Instant::now();
+        let rows = covering_array(&sizes, 3);
+        let elapsed = start.elapsed();
+
+        let report = coverage(&sizes, 3, &rows);
+        assert_eq!(report.covered, report.combinations);
+        assert!(rows.len() < 2_000, "{} rows", rows.len());
+        // under a second in a debug build, rescanning every uncovered combination for each
+        // candidate value took over ten seconds even in release
+        assert!(elapsed.as_secs() < 10, "took {:?}", elapsed);
+    }
+
+    #[test]
+    fn coverage_report() {
+        let sizes = [2, 2, 2];
+        let rows = vec![vec![0, 0, 0], vec![1, 1, 1]];
+        let report = coverage(&sizes, 2, &rows);
+        assert_eq!(12, report.combinations);
+        assert_eq!(6, report.covered);
+        assert_eq!(8, report.full_product);
+        assert_eq!(0.5, report.ratio());
+        assert_eq!(0.25, report.size_ratio());
+        assert_eq!(
+            "2-wise coverage 50.0% (6 of 12 combinations) with 2 of 8 variants (25.00%)",
+            report.to_string()
+        );
+    }
+
+    #[test]
+    fn smaller_than_the_product() {
+        let rows = pairwise(&[3, 3, 3, 3]);
+        assert!(rows.len() <= 12, "{} rows", rows.len());
+
+        let rows = pairwise(&[2; 10]);
+        assert!(rows.len() <= 10, "{} rows", rows.len());
+    }
+
+    #[test]
+    fn edge_cases() {
+        assert_eq!(vec![vec![0], vec![1], vec![2]], pairwise(&[3]));
+        assert_eq!(vec![Vec::<usize>::new()], pairwise(&[]));
+        assert!(pairwise(&[3, 0, 2]).is_empty());
+    }
+
+    #[test]
+    fn every_value_one_at_a_time() {
+        let rows = one_at_a_time(&[2, 1, 3]);
+        assert_eq!(
+            vec![vec![0, 0, 0], vec![1, 0, 0], vec![0, 0, 1], vec![0, 0, 2]],
+            rows
+        );
+        assert_eq!(1.0, coverage(&[2, 1, 3], 1, &rows).ratio());
+        assert_eq!(vec![Vec::<usize>::new()], one_at_a_time(&[]));
+        assert!(one_at_a_time(&[3, 0, 2]).is_empty());
+    }
+
+    #[test]
+    fn combinations_in_order() {
+        assert_eq!(vec![vec![0, 1], vec![0, 2], vec![1, 2]], combinations(3, 2));
+        assert_eq!(vec![Vec::<usize>::new()], combinations(3, 0));
+        assert!(combinations(2, 3).is_empty());
+    }
+}
@@ -0,0 +1,119 @@
+//! Keeps recursive types such as `enum Expr { Lit(i32), Add(Box<Expr>, Box<Expr>) }` from
+//! generating variants forever.
+//!
+//! The pointers a type can recurse through, like `Box` and `Vec`, generate their contents one
+//! level deeper into that type. Once a type is nested `max_depth` levels deep in itself, the
+//! pointer acts as if the type had no variants, so only its non-recursive variants are left.
+
+use crate::EveryVariant;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DEFAULT_MAX_DEPTH: AtomicUsize = AtomicUsize::new(2);
+
+thread_local! {
+    /// How deep every type is nested in itself right now, by type name
+    static DEPTHS: RefCell<HashMap<&'static str, usize>> = RefCell::new(HashMap::new());
+}
+
+/// How deep types without `#[every_variant(max_depth = ...)]` can be nested in themselves
+pub fn default_max_depth() -> usize {
+    DEFAULT_MAX_DEPTH.load(Ordering::Relaxed)
+}
+
+/// Changes the depth for types without `#[every_variant(max_depth = ...)]`. The counts and
+/// indexes of recursive types change with it, so set it before generating any of them.
+pub fn set_default_max_depth(depth: usize) {
+    DEFAULT_MAX_DEPTH.store(depth, Ordering::Relaxed);
+}
+
+/// How deep `T` is nested in itself right now
+pub fn current_depth<T: EveryVariant>() -> usize {
+    DEPTHS.with(|depths| {
+        depths
+            .borrow()
+            .get(std::any::type_name::<T>())
+            .copied()
+            .unwrap_or(0)
+    })
+}
+
+/// The default depth and how deep every type is nested right now, sorted by type name. The
+/// variants generated for a type only change when this does.
+pub(crate) fn state() -> (usize, Vec<(&'static str, usize)>) {
+    let mut depths: Vec<_> = DEPTHS.with(|depths| {
+        depths
+            .borrow()
+            .iter()
+            .map(|(name, depth)| (*name, *depth))
+            .collect()
+    });
+    depths.sort_unstable();
+    (default_max_depth(), depths)
+}
+
+/// Calls `f` one level deeper into `T`, or returns `None` when `T` is already nested
+/// `T::max_depth()` levels deep
+pub fn nested<T: EveryVariant, R, F: FnOnce() -> R>(f: F) -> Option<R> {
+    if current_depth::<T>() >= T::max_depth() {
+        return None;
+    }
+
+    let _level = Level::enter(std::any::type_name::<T>());
+    Some(f())
+}
+
+/// One level of nesting, left again when dropped, also when `f` panics
+struct Level(&'static str);
+
+impl Level {
+    fn enter(name: &'static str) -> Self {
+        DEPTHS.with(|depths| *depths.borrow_mut().entry(name).or_insert(0) += 1);
+        Level(name)
+    }
+}
+
+impl Drop for Level {
+    fn drop(&mut self) {
+        DEPTHS.with(|depths| {
+            let mut depths = depths.borrow_mut();
+            if let Some(depth) = depths.get_mut(self.0) {
+                *depth -= 1;
+                if *depth == 0 {
+                    depths.remove(self.0);
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nests_up_to_max_depth() {
+        assert_eq!(2, default_max_depth());
+        assert_eq!(0, current_depth::<bool>());
+
+        let depths = nested::<bool, _, _>(|| {
+            let inner = nested::<bool, _, _>(|| {
+                let other = nested::<u8, _, _>(current_depth::<u8>);
+                let too_deep = nested::<bool, _, _>(|| ());
+                (current_depth::<bool>(), other, too_deep)
+            });
+            (current_depth::<bool>(), inner)
+        });
+
+        assert_eq!(Some((1, Some((2, Some(1), None)))), depths);
+        assert_eq!(0, current_depth::<bool>());
+    }
+
+    #[test]
+    fn leaves_the_level_on_panic() {
+        let result = std::panic::catch_unwind(|| nested::<u16, (), _>(|| panic!("inside")));
+        assert!(result.is_err());
+        assert_eq!(0, current_depth::<u16>());
+    }
+}
@@ -0,0 +1,336 @@
+//! Values at the edges of what a type can hold, where overflow, off-by-one, sign and NaN bugs
+//! show up. These are opt-in, the variants of the types themselves stay the same: mark a field with
+//! `#[every_variant(edge_cases)]`, or wrap the type in [`EdgeCase`].
+
+use crate::EveryVariant;
+use std::convert::TryFrom;
+use std::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize,
+};
+use std::sync::OnceLock;
+
+/// Types with a set of values at the edges of their range
+pub trait EdgeCases: Sized + PartialEq {
+    /// The edge values, without duplicates
+    fn edge_cases() -> Vec<Self>;
+    /// The position of this value in `edge_cases`. Floats compare their bits, so NaN is found
+    /// as well, and `-0.0` is told apart from `0.0`.
+    fn edge_case_index(&self) -> Option<usize> {
+        Self::edge_cases().iter().position(|edge| edge == self)
+    }
+}
+
+/// A value of `T` taken from its edge cases, so `EdgeCase<u8>::every_variant()` gives the
+/// edges of `u8` where `u8::every_variant()` gives a single value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EdgeCase<T>(pub T);
+
+impl<T: EdgeCases> EveryVariant for EdgeCase<T> {
+    fn every_variant() -> Vec<Self> {
+        T::edge_cases().into_iter().map(EdgeCase).collect()
+    }
+
+    fn variant_index(&self) -> Option<usize> {
+        self.0.edge_case_index()
+    }
+}
+
+/// Around every power of two that is the edge of a smaller integer type, as values there
+/// change when they are cast
+const POWERS_OF_TWO: [u32; 8] = [7, 8, 15, 16, 31, 32, 63, 64];
+
+/// Pushes `value` unless it is already there
+fn push_new<T: PartialEq>(values: &mut Vec<T>, value: Option<T>) {
+    if let Some(value) = value {
+        if !values.contains(&value) {
+            values.push(value);
+        }
+    }
+}
+
+/// The `values` of a leaf type followed by its edge cases that are not among them, its
+/// variants in the exhaustive profile
+pub(crate) fn with_edge_cases<T: EdgeCases>(mut values: Vec<T>) -> Vec<T> {
+    let variants = values.len();
+    for edge in T::edge_cases() {
+        if !values[..variants].contains(&edge) {
+            values.push(edge);
+        }
+    }
+    values
+}
+
+macro_rules! int_edge_cases {
+    ($($int: ty => $non_zero: ty),+) => {
+        $(
+            /// 0, 1, -1, the minimum and maximum and their neighbours, and the values around
+            /// powers of two where smaller integer types end
+            impl EdgeCases for $int {
+                fn edge_cases() -> Vec<Self> {
+                    let mut values = Vec::new();
+                    push_new(&mut values, Some(0));
+                    push_new(&mut values, Some(1));
+                    push_new(&mut values, Self::try_from(-1i128).ok());
+                    push_new(&mut values, Some(Self::MIN));
+                    push_new(&mut values, Self::MIN.checked_add(1));
+                    push_new(&mut values, Some(Self::MAX));
+                    push_new(&mut values, Self::MAX.checked_sub(1));
+
+                    for &bits in POWERS_OF_TWO.iter() {
+                        let power = 1i128 << bits;
+                        for &edge in [power - 1, power, -power, -power - 1].iter() {
+                            push_new(&mut values, Self::try_from(edge).ok());
+                        }
+                    }
+                    values
+                }
+            }
+
+            /// The edge cases of the integer, without 0
+            impl EdgeCases for $non_zero {
+                fn edge_cases() -> Vec<Self> {
+                    <$int>::edge_cases()
+                        .into_iter()
+                        .filter_map(Self::new)
+                        .collect()
+                }
+            }
+        )+
+    };
+}
+
+int_edge_cases!(
+    u8 => NonZeroU8,
+    u16 => NonZeroU16,
+    u32 => NonZeroU32,
+    u64 => NonZeroU64,
+    u128 => NonZeroU128,
+    usize => NonZeroUsize,
+    i8 => NonZeroI8,
+    i16 => NonZeroI16,
+    i32 => NonZeroI32,
+    i64 => NonZeroI64,
+    i128 => NonZeroI128,
+    isize => NonZeroIsize
+);
+
+macro_rules! float_edge_cases {
+    ($($float: ty),+) => {
+        $(
+            /// Both zeros, the smallest normal and subnormal values, EPSILON, the minimum and
+            /// maximum, both infinities, and NaN both plain and with a payload
+            impl EdgeCases for $float {
+                fn edge_cases() -> Vec<Self> {
+                    vec![
+                        0.0,
+                        -0.0,
+                        Self::MIN_POSITIVE,
+                        Self::from_bits(1),
+                        Self::EPSILON,
+                        Self::MAX,
+                        Self::MIN,
+                        Self::INFINITY,
+                        Self::NEG_INFINITY,
+                        Self::NAN,
+                        Self::from_bits(Self::NAN.to_bits() | 1),
+                    ]
+                }
+
+                fn edge_case_index(&self) -> Option<usize> {
+                    Self::edge_cases()
+                        .iter()
+                        .position(|edge| edge.to_bits() == self.to_bits())
+                }
+            }
+        )+
+    };
+}
+
+float_edge_cases!(f32, f64);
+
+/// Strings that parsers, formatters and anything slicing by bytes tend to get wrong
+const STRINGS: [&str; 32] = [
+    // empty and whitespace only
+    "",
+    " ",
+    " \t\r\n ",
+    "\u{a0}\u{2003}\u{3000}",
+    // multibyte, combining characters, right to left overrides and emoji sequences
+    "héllo wörld",
+    "日本語のテキスト",
+    "𝕳𝖊𝖑𝖑𝖔",
+    "e\u{301}",
+    "Z\u{351}\u{36b}\u{343}\u{36a}a\u{302}\u{36b}\u{33d}\u{34f}l\u{334}g\u{30c}o\u{362}",
+    "\u{202e}fdp.exe",
+    "abc\u{200f}\u{5d0}\u{5d1}",
+    "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}\u{200d}\u{1f466}",
+    "\u{1f1f8}\u{1f1ea}",
+    "\u{feff}bom",
+    // control characters
+    "nul\0byte",
+    "line\r\nbreak\n",
+    "\u{1b}[31mred\u{1b}[0m",
+    // numbers that are not quite numbers
+    "0",
+    "-1",
+    "65536",
+    "1e309",
+    "NaN",
+    "0x1F",
+    " 42 ",
+    "+7",
+    "1_000",
+    "\u{661}\u{662}\u{663}",
+    // format strings and injection
+    "{}{0}{:?}",
+    "%s%n%x",
+    "'; DROP TABLE users; --",
+    "<script>alert(1)</script>",
+    "../../../etc/passwd",
+];
+
+/// Longer than what fits in a `u16` length
+fn long_string() -> &'static str {
+    static LONG: OnceLock<String> = OnceLock::new();
+    LONG.get_or_init(|| "a".repeat(1 << 16))
+}
+
+/// The empty string, whitespace, multibyte UTF-8, combining characters, right to left
+/// overrides, emoji sequences, control characters, numeric looking text, format strings,
+/// injection attempts and a very long string
+impl EdgeCases for &'static str {
+    fn edge_cases() -> Vec<Self> {
+        let mut strings = STRINGS.to_vec();
+        strings.push(long_string());
+        strings
+    }
+}
+
+/// The same strings as `&'static str`
+impl EdgeCases for String {
+    fn edge_cases() -> Vec<Self> {
+        <&'static str>::edge_cases()
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+}
+
+/// The strings of `&'static str` that fit, and one filling the whole capacity
+#[cfg(feature = "ev_heapless")]
+impl<const N: usize> EdgeCases for heapless::String<N> {
+    fn edge_cases() -> Vec<Self> {
+        let full = "a".repeat(N);
+        let mut strings = Vec::new();
+        for s in <&'static str>::edge_cases()
+            .into_iter()
+            .chain(std::iter::once(full.as_str()))
+        {
+            let mut string = heapless::String::new();
+            if string.push_str(s).is_ok() {
+                push_new(&mut strings, Some(string));
+            }
+        }
+        strings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integer_edges() {
+        assert_eq!(vec![0, 1, 255, 254, 127, 128], u8::edge_cases());
+        assert_eq!(vec![0, 1, -1, -128, -127, 127, 126], i8::edge_cases());
+        assert_eq!(
+            vec![
+                0,
+                1,
+                -1,
+                i16::MIN,
+                i16::MIN + 1,
+                i16::MAX,
+                i16::MAX - 1,
+                127,
+                128,
+                -128,
+                -129,
+                255,
+                256,
+                -256,
+                -257
+            ],
+            i16::edge_cases()
+        );
+
+        let u64s = u64::edge_cases();
+        assert!(u64s.contains(&u64::MAX));
+        assert!(u64s.contains(&(u32::MAX as u64 + 1)));
+        assert!(u64s.contains(&(1 << 63)));
+        assert!(u128::edge_cases().contains(&(u64::MAX as u128 + 1)));
+        assert!(i128::edge_cases().contains(&i128::MIN));
+    }
+
+    #[test]
+    fn non_zero_edges() {
+        let edges: Vec<u8> = NonZeroU8::edge_cases()
+            .into_iter()
+            .map(|n| n.get())
+            .collect();
+        assert_eq!(vec![1, 255, 254, 127, 128], edges);
+        assert_eq!(i32::edge_cases().len() - 1, NonZeroI32::edge_cases().len());
+    }
+
+    #[test]
+    fn float_edges() {
+        let edges = f64::edge_cases();
+        assert_eq!(11, edges.len());
+        assert!(edges[1] == 0.0 && edges[1].is_sign_negative());
+        assert!(edges[3] > 0.0 && !edges[3].is_normal());
+        assert!(edges[9].is_nan() && edges[10].is_nan());
+        assert_ne!(edges[9].to_bits(), edges[10].to_bits());
+        assert!(f32::edge_cases().contains(&f32::NEG_INFINITY));
+
+        let indexes: Vec<Option<usize>> = edges.iter().map(EdgeCases::edge_case_index).collect();
+        assert_eq!((0..11).map(Some).collect::<Vec<_>>(), indexes);
+        assert_eq!(None, 2.5f64.edge_case_index());
+    }
+
+    #[test]
+    fn string_edges() {
+        let strings = String::edge_cases();
+        assert_eq!(STRINGS.len() + 1, strings.len());
+        assert!(strings.contains(&String::new()));
+        assert!(strings.iter().any(|s| s.contains('\0')));
+        assert!(strings.iter().any(|s| s.len() > usize::from(u16::MAX)));
+        assert!(strings.iter().any(|s| s.len() != s.chars().count()));
+        assert!(strings.iter().any(|s| !s.is_empty() && s.trim().is_empty()));
+
+        let mut sorted = strings.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(strings.len(), sorted.len());
+        assert_eq!(Some(20), EdgeCase(String::from("1e309")).variant_index());
+    }
+
+    #[cfg(feature = "ev_heapless")]
+    #[test]
+    fn heapless_string_edges() {
+        let strings = heapless::String::<4>::edge_cases();
+        assert!(strings.iter().all(|s| s.len() <= 4));
+        assert!(strings.iter().any(|s| s.is_empty()));
+        assert_eq!(Some(&"aaaa"), strings.last().map(|s| s.as_str()).as_ref());
+        assert!(heapless::String::<64>::edge_cases().len() > strings.len());
+    }
+
+    #[test]
+    fn edge_case_variants() {
+        assert_eq!(u8::edge_cases().len(), EdgeCase::<u8>::variant_count());
+        assert_eq!(Some(2), EdgeCase(255u8).variant_index());
+        assert_eq!(None, EdgeCase(3u8).variant_index());
+        // the default set stays small
+        assert_eq!(vec![8], u8::every_variant());
+    }
+}
@@ -2,11 +2,8 @@ use crate::*;
 
 use std::str::FromStr;
 
-// plans to help add crashtesting are just examined in this file, please ignore
-
-
 #[allow(unused)]
-#[crashtest_every_variant] // <- this should create the function crashtest_parse_double_strings_into_numbers() -> ()
+#[crashtest_every_variant] // <- this creates the function crashtest_parse_double_strings_into_numbers() -> ()
 fn parse_double_strings_into_numbers(s1: String, s2: String) -> (u16, u16) {
     let s1: u16 = u16::from_str(&s1).unwrap_or_default();
     let s2: u16 = u16::from_str(&s2).unwrap_or_default();
@@ -15,37 +12,75 @@ fn parse_double_strings_into_numbers(s1: String, s2: String) -> (u16, u16) {
 }
 
 #[allow(unused)]
-#[crashtest_every_variant] // <- this should create the function crashtest_parse_single_string() -> ()
+#[crashtest_every_variant] // <- this creates the function crashtest_parse_single_string() -> ()
 fn parse_single_string(s1: String) -> u16 {
     let s1: u16 = u16::from_str(&s1).unwrap_or_default();
 
     s1
 }
 
+#[allow(unused)]
+#[crashtest_every_variant]
+fn append_parsed(numbers: &mut Vec<u16>, s1: &str, fallback: &Option<u16>) {
+    numbers.push(u16::from_str(s1).ok().or(*fallback).unwrap_or_default());
+}
+
+#[allow(unused)]
+#[crashtest_every_variant]
+fn unwrap_number(number: Option<u8>) -> u8 {
+    number.unwrap()
+}
+
+#[allow(unused)]
+#[crashtest_every_variant]
+fn parse_with_fallback(#[every_variant(edge_cases)] s1: &str, fallback: Option<u16>) -> u16 {
+    s1.trim().parse().ok().or(fallback).unwrap_or_default()
+}
+
+/// Slices by bytes, which breaks on empty and multibyte strings
+#[allow(unused)]
+#[crashtest_every_variant]
+fn capitalize(#[every_variant(edge_cases)] s1: &str) -> String {
+    s1[..1].to_uppercase() + &s1[1..]
+}
+
+mod other {
+    use crate::*;
+
+    #[allow(unused)]
+    #[crashtest_every_variant]
+    pub fn parse_single_string(s1: String) -> u16 {
+        s1.parse().unwrap_or_default()
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    pub use super::*;
+    use super::*;
 
-    // this should just use the created function, and create tests for them all
-    craschtests!{
+    // this uses the created functions, and creates tests for them all
+    craschtests! {
         parse_double_strings_into_numbers,
         parse_single_string,
-    };
+        append_parsed,
+        parse_with_fallback,
+        other::parse_single_string,
+    }
 
-    // like this ->
     #[test]
-    fn crashtest_parse_double_strings_into_numbers() {
-        for s1 in String::every_variant() {
-            for s2 in String::every_variant() {
-                let _ = parse_double_strings_into_numbers(s1.clone(), s2.clone());
-            }
-        }
+    #[should_panic(
+        expected = "unwrap_number panicked with arguments: number = None: called \
+                               `Option::unwrap()` on a `None` value"
+    )]
+    fn crashtest_panics() {
+        crashtest_unwrap_number();
     }
 
     #[test]
-    fn crashtest_parse_single_string() {
-        for s1 in String::every_variant() {
-            let _ = parse_single_string(s1.clone());
-        }
+    #[should_panic(expected = "byte index 1")]
+    fn crashtest_edge_cases_panic() {
+        // passes with the single example string `every_variant` gives a `String`
+        assert_eq!("Example String", capitalize("example String"));
+        crashtest_capitalize();
     }
 }
@@ -1,19 +1,248 @@
 pub use every_variant_macro::*;
 
+// lets the paths in the derive output resolve inside this crate as well
+extern crate self as every_variant;
+
 /// Trait that supplies a function to generate a vector containing all possible variants in a tree
 pub trait EveryVariant: Sized {
     /// A vector of variants that should contain every possible variant of the struct or enum
     fn every_variant() -> Vec<Self>;
-    /// Helper function, loops of each variant in an enum. Else does nothing.
+    /// Same variants as `every_variant`, in the same order, but generated one at a time so
+    /// large trees can be walked without holding them all in memory
+    fn every_variant_iter() -> impl Iterator<Item = Self> {
+        Self::every_variant().into_iter()
+    }
+    /// A much smaller set than `every_variant`, that still has every enum variant at every level
+    /// of the tree in at least one value. Derived types go through the shallow values of each
+    /// field in turn, the other fields keeping their first one, so the number of values is the
+    /// sum over the fields instead of their product.
+    fn every_variant_shallow() -> Vec<Self> {
+        Self::every_variant()
+    }
+    /// A larger set than `every_variant`: leaf types with [`EdgeCases`], such as integers,
+    /// floats and strings, add them after their usual values, and containers and derived types
+    /// combine the exhaustive values of what they hold like `every_variant` combines variants.
+    /// Grows quickly, meant for thorough runs such as a nightly build.
+    fn every_variant_exhaustive() -> Vec<Self> {
+        Self::every_variant_exhaustive_iter().collect()
+    }
+    /// Same values as `every_variant_exhaustive`, in the same order, generated one at a time.
+    /// See [`profile::exhaustively`] to count, pick or index them as well.
+    fn every_variant_exhaustive_iter() -> impl Iterator<Item = Self> {
+        Exhaustive::variants_iter()
+    }
+    /// The values the profile `P` gives for this type, such as
+    /// `Message::every_variant_in::<Exhaustive>()`, see [`profile`]
+    fn every_variant_in<P: Profile>() -> Vec<Self> {
+        P::variants()
+    }
+    /// The values of the profile named in `EVERY_VARIANT_PROFILE`, `standard` when it is not
+    /// set, so the same tests can run quickly or thoroughly
+    fn every_variant_from_env() -> Vec<Self> {
+        profile::ProfileKind::from_env().variants()
+    }
+    /// The number of variants `every_variant` returns, without generating them when the type
+    /// knows better. Saturates at `usize::MAX` for trees too large to count.
+    fn variant_count() -> usize {
+        Self::every_variant_iter().count()
+    }
+    /// The variant at `index` in `every_variant`, or `None` past the end. Derived types work it
+    /// out from the counts of their fields, without generating the variants before it.
+    fn nth_variant(index: usize) -> Option<Self> {
+        Self::every_variant_iter().nth(index)
+    }
+    /// The index of this value in `every_variant`, or `None` when it is not one of them. Derived
+    /// types and containers work it out from their fields, only the leaf types compare values,
+    /// fields marked with `#[every_variant(skip)]` are ignored. Fields with listed values only
+    /// have an index when marked with `partial_eq` as well. Manual implementations can use
+    /// [`position_of`] when the type implements `PartialEq`.
+    fn variant_index(&self) -> Option<usize> {
+        None
+    }
+    /// Appends the path of every enum variant in this type, at every level of nesting, to
+    /// `paths`. A path names the fields leading to the variant, such as
+    /// `Message::Hello.text.Option::Some`, and starts with `prefix`. Fields marked with
+    /// `#[every_variant(skip)]` or `#[every_variant(values = ...)]` are left out.
+    fn variant_paths(prefix: &str, paths: &mut Vec<String>) {
+        let _ = (prefix, paths);
+    }
+    /// Appends the paths of the enum variants this value is made of to `paths`, the same paths
+    /// as `variant_paths` lists, without comparing any leaf values
+    fn observed_variant_paths(&self, prefix: &str, paths: &mut Vec<String>) {
+        let _ = (prefix, paths);
+    }
+    /// How many levels deep this type is generated inside itself, for recursive types. Set with
+    /// `#[every_variant(max_depth = ...)]`, see [`depth`].
+    fn max_depth() -> usize {
+        depth::default_max_depth()
+    }
+    /// Splits the variants into `shards` contiguous ranges, and walks the one numbered
+    /// `shard` together with the index of each variant
+    fn shard_variants(shard: usize, shards: usize) -> impl Iterator<Item = (usize, Self)> {
+        Standard::shard_variants(shard, shards)
+    }
+    /// A smaller set of variants, in which every combination of values of any `strength`
+    /// fields shows up at least once, together with how well it covers them and how it
+    /// compares to `every_variant`. Nested types contribute their own `strength` combinations,
+    /// types without fields return every variant.
+    fn covering_variants(strength: usize) -> (Vec<Self>, Coverage) {
+        let vec = Self::every_variant();
+        let coverage = Coverage::complete(strength, vec.len());
+        (vec, coverage)
+    }
+    /// The variants of `covering_variants`, 3 covers every combination of any three fields
+    fn every_t_wise(strength: usize) -> Vec<Self> {
+        Self::covering_variants(strength).0
+    }
+    /// A smaller set of variants, in which every pair of values of any two fields shows up at
+    /// least once
+    fn every_pair() -> Vec<Self> {
+        Self::every_t_wise(2)
+    }
+    /// One variant picked at random, or `None` for types without variants
+    fn random_variant(rng: &mut Rng) -> Option<Self> {
+        let count = Self::variant_count();
+        if count == 0 {
+            return None;
+        }
+        Self::nth_variant(rng.below(count))
+    }
+    /// `n` variants picked at random, the same `seed` always picks the same variants. Derived
+    /// types pick each field and enum variant on its own, without generating every variant.
+    fn sample_variants(seed: u64, n: usize) -> Vec<Self> {
+        let mut rng = Rng::new(seed);
+        (0..n)
+            .map_while(|_| Self::random_variant(&mut rng))
+            .collect()
+    }
+    /// Calls the closure with `n` variants picked at random, see `sample_variants`. If the
+    /// closure panics it panics again with the seed in the message, so the run can be repeated.
+    fn for_sampled_variants<F: FnMut(&Self)>(seed: u64, n: usize, closure: F) {
+        random::for_samples(seed, n, closure, &format!("with seed {}", seed));
+    }
+    /// Same as `for_sampled_variants`, with the seed from [`random::seed_from_env`]. A panic
+    /// names the `EVERY_VARIANT_SEED` that repeats the run.
+    fn for_sampled_variants_from_env<F: FnMut(&Self)>(n: usize, closure: F) {
+        let seed = random::seed_from_env();
+        let rerun = format!(
+            "with seed {}, rerun with {}={}",
+            seed,
+            random::SEED_VARIABLE,
+            seed
+        );
+        random::for_samples(seed, n, closure, &rerun);
+    }
+    /// Helper function, calls the closure with each variant in turn
     fn for_every_variant<F: Fn(&Self)>(closure: F) {
-        // Do nothing
-        let _ = closure;
+        Self::for_every_variant_mut(closure)
+    }
+    /// Same as `for_every_variant`, for closures that keep state between the variants
+    fn for_every_variant_mut<F: FnMut(&Self)>(closure: F) {
+        Self::for_every_variant_in::<Standard, F>(closure)
+    }
+    /// Calls the closure with each value of the profile `P` in turn, generated one at a time,
+    /// such as `Message::for_every_variant_in::<Exhaustive, _>(|m| ...)`
+    fn for_every_variant_in<P: Profile, F: FnMut(&Self)>(mut closure: F) {
+        for value in P::variants_iter() {
+            closure(&value);
+        }
+    }
+    /// Same as `for_every_variant_in`, with the profile named in `EVERY_VARIANT_PROFILE`
+    fn for_every_variant_from_env<F: FnMut(&Self)>(closure: F) {
+        profile::ProfileKind::from_env().for_every_variant(closure)
+    }
+    /// Calls the closure with each variant in turn until it returns `Err` or
+    /// `ControlFlow::Break`, and returns that variant with its index and the error
+    fn try_for_every_variant<R, F>(mut closure: F) -> Result<(), VariantFailure<Self, R::Failure>>
+    where
+        R: Outcome,
+        F: FnMut(&Self) -> R,
+    {
+        for (index, value) in Self::every_variant_iter().enumerate() {
+            if let Some(error) = closure(&value).failure() {
+                return Err(VariantFailure {
+                    index,
+                    value,
+                    error,
+                });
+            }
+        }
+        Ok(())
+    }
+    /// Same as `try_for_every_variant`, with the variants spread over several threads. Each
+    /// thread generates its own range of variants with `nth_variant`, and every failure is
+    /// collected instead of stopping at the first, ordered by index. Uses rayon with the
+    /// `rayon` feature, scoped threads otherwise, see [`parallel`].
+    fn par_for_every_variant<R, F>(closure: F) -> Result<(), Vec<VariantFailure<Self, R::Failure>>>
+    where
+        Self: Send,
+        R: Outcome,
+        R::Failure: Send,
+        F: Fn(&Self) -> R + Sync,
+    {
+        Self::par_for_every_variant_in::<Standard, R, F>(closure)
+    }
+    /// Same as `par_for_every_variant`, with the values of the profile `P`. Each thread
+    /// generates its own range with `P::shard_variants`, also for the exhaustive profile.
+    fn par_for_every_variant_in<P, R, F>(
+        closure: F,
+    ) -> Result<(), Vec<VariantFailure<Self, R::Failure>>>
+    where
+        Self: Send,
+        P: Profile,
+        R: Outcome,
+        R::Failure: Send,
+        F: Fn(&Self) -> R + Sync,
+    {
+        #[cfg(feature = "rayon")]
+        return parallel::for_every_variant_in_rayon::<P, Self, R, F>(closure);
+        #[cfg(not(feature = "rayon"))]
+        return parallel::for_every_variant_in_on::<P, Self, R, F>(
+            parallel::available_threads(),
+            closure,
+        );
+    }
+}
+
+/// The index of `value` in `T::every_variant()`, by comparing it to every variant
+pub fn position_of<T: EveryVariant + PartialEq>(value: &T) -> Option<usize> {
+    T::every_variant_iter().position(|variant| &variant == value)
+}
+
+/// Whether `a` and `b` are the same variant, by comparing their `variant_index` among the
+/// exhaustive values, which include every variant. Unlike `==` this holds for NaN from the float
+/// edge cases, and tells `-0.0` from `0.0`, so it also works for derived types containing
+/// floats. Values that are not among the exhaustive values, such as `2.5`, are compared with
+/// `==` instead.
+pub fn variant_eq<T: EveryVariant + PartialEq>(a: &T, b: &T) -> bool {
+    match profile::exhaustively(|| (a.variant_index(), b.variant_index())) {
+        (Some(a), Some(b)) => a == b,
+        _ => a == b,
     }
 }
 
+pub mod covering;
+pub use covering::Coverage;
+pub mod depth;
+pub mod edge_cases;
+pub use edge_cases::{EdgeCase, EdgeCases};
+#[cfg(test)]
+mod function_test;
+pub mod outcome;
+pub use outcome::{Outcome, VariantFailure};
+pub mod parallel;
+pub mod product;
+pub mod profile;
+pub use profile::{Exhaustive, Minimal, Profile, Standard};
+pub mod random;
+pub use random::Rng;
 pub mod std_impl;
 pub use std_impl::*;
+pub mod tracking;
+pub use tracking::VariantCoverage;
 #[cfg(feature = "uuid")]
 pub mod uuid;
+// the module only holds impls for now, the re-export keeps paths into it working
 #[cfg(feature = "uuid")]
+#[allow(unused_imports)]
 pub use uuid::*;
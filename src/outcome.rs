@@ -0,0 +1,72 @@
+//! What the closures passed to `try_for_every_variant` return, and the variant they failed on.
+
+use std::any::Any;
+use std::fmt;
+use std::ops::ControlFlow;
+
+/// The result of a closure called with a variant, either going on to the next variant or
+/// stopping with a failure
+pub trait Outcome {
+    /// What is reported when the closure stops
+    type Failure;
+
+    /// The failure to stop with, `None` to go on
+    fn failure(self) -> Option<Self::Failure>;
+}
+
+impl Outcome for () {
+    type Failure = std::convert::Infallible;
+
+    fn failure(self) -> Option<Self::Failure> {
+        None
+    }
+}
+
+impl<E> Outcome for Result<(), E> {
+    type Failure = E;
+
+    fn failure(self) -> Option<E> {
+        self.err()
+    }
+}
+
+impl<B> Outcome for ControlFlow<B> {
+    type Failure = B;
+
+    fn failure(self) -> Option<B> {
+        match self {
+            ControlFlow::Continue(()) => None,
+            ControlFlow::Break(value) => Some(value),
+        }
+    }
+}
+
+/// The variant a closure stopped on, with its index in `every_variant` and what the closure
+/// returned
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariantFailure<T, E> {
+    pub index: usize,
+    pub value: T,
+    pub error: E,
+}
+
+impl<T: fmt::Debug, E: fmt::Debug> fmt::Display for VariantFailure<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "variant {} failed with {:?}: {:?}",
+            self.index, self.error, self.value
+        )
+    }
+}
+
+impl<T: fmt::Debug, E: fmt::Debug> std::error::Error for VariantFailure<T, E> {}
+
+/// The message a closure panicked with, `panic!` leaves either a `&str` or a `String`
+pub fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("a value that is not a string")
+}
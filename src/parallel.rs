@@ -0,0 +1,184 @@
+//! Running a closure with every variant on several threads. The variants are split into
+//! ranges of indexes, each generated on its own with `shard_variants`, so apart from the
+//! shallow values of the minimal profile they are never all held in memory.
+
+use crate::{EveryVariant, Outcome, Profile, Standard, VariantFailure};
+
+/// The number of threads `par_for_every_variant` uses without rayon, one per core
+pub fn available_threads() -> usize {
+    std::thread::available_parallelism().map_or(1, |threads| threads.get())
+}
+
+/// Calls the closure with every variant of `T` on `threads` scoped threads, each walking a
+/// contiguous range of `T::shard_variants`, and returns every failure ordered by index
+pub fn for_every_variant_on<T, R, F>(
+    threads: usize,
+    closure: F,
+) -> Result<(), Vec<VariantFailure<T, R::Failure>>>
+where
+    T: EveryVariant + Send,
+    R: Outcome,
+    R::Failure: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    for_every_variant_in_on::<Standard, T, R, F>(threads, closure)
+}
+
+/// Same as [`for_every_variant_on`], with the values of the profile `P`
+pub fn for_every_variant_in_on<P, T, R, F>(
+    threads: usize,
+    closure: F,
+) -> Result<(), Vec<VariantFailure<T, R::Failure>>>
+where
+    P: Profile,
+    T: EveryVariant + Send,
+    R: Outcome,
+    R::Failure: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let threads = threads.max(1);
+    let closure = &closure;
+
+    let failures: Vec<VariantFailure<T, R::Failure>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|shard| {
+                scope.spawn(move || {
+                    P::shard_variants(shard, threads)
+                        .filter_map(|(index, value)| {
+                            let error = closure(&value).failure()?;
+                            Some(VariantFailure {
+                                index,
+                                value,
+                                error,
+                            })
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        // the shards are in order, and so are the failures within each of them
+        handles
+            .into_iter()
+            .flat_map(|handle| match handle.join() {
+                Ok(failures) => failures,
+                Err(panic) => std::panic::resume_unwind(panic),
+            })
+            .collect()
+    });
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures)
+    }
+}
+
+/// Same as [`for_every_variant_on`], on the global rayon thread pool
+#[cfg(feature = "rayon")]
+pub fn for_every_variant_rayon<T, R, F>(
+    closure: F,
+) -> Result<(), Vec<VariantFailure<T, R::Failure>>>
+where
+    T: EveryVariant + Send,
+    R: Outcome,
+    R::Failure: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    for_every_variant_in_rayon::<Standard, T, R, F>(closure)
+}
+
+/// Same as [`for_every_variant_rayon`], with the values of the profile `P`, walking a range of
+/// `P::shard_variants` for each thread of the pool
+#[cfg(feature = "rayon")]
+pub fn for_every_variant_in_rayon<P, T, R, F>(
+    closure: F,
+) -> Result<(), Vec<VariantFailure<T, R::Failure>>>
+where
+    P: Profile,
+    T: EveryVariant + Send,
+    R: Outcome,
+    R::Failure: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    use rayon::prelude::*;
+
+    // a shard for each thread of the pool, in order, so values that are generated together
+    // are only generated once for each shard
+    let shards = rayon::current_num_threads();
+    let failures: Vec<VariantFailure<T, R::Failure>> = (0..shards)
+        .into_par_iter()
+        .flat_map_iter(|shard| P::shard_variants::<T>(shard, shards))
+        .filter_map(|(index, value)| {
+            let error = closure(&value).failure()?;
+            Some(VariantFailure {
+                index,
+                value,
+                error,
+            })
+        })
+        .collect();
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn collects_every_failure_in_order() {
+        for threads in [1, 3, 8, 40] {
+            let calls = AtomicUsize::new(0);
+            let failures = for_every_variant_on(threads, |value: &(Option<bool>, [bool; 2])| {
+                calls.fetch_add(1, Ordering::Relaxed);
+                match value.0 {
+                    Some(true) => Err(value.1[0]),
+                    _ => Ok(()),
+                }
+            })
+            .unwrap_err();
+
+            assert_eq!(
+                <(Option<bool>, [bool; 2])>::variant_count(),
+                calls.into_inner()
+            );
+            let indexes: Vec<usize> = failures.iter().map(|failure| failure.index).collect();
+            assert_eq!(vec![4, 5, 6, 7], indexes);
+            assert!(failures
+                .iter()
+                .all(|failure| failure.value.1[0] == failure.error));
+        }
+    }
+
+    #[test]
+    fn par_for_every_variant() {
+        assert_eq!(Ok(()), <(u8, Option<bool>)>::par_for_every_variant(|_| ()));
+
+        let failures = Vec::<bool>::par_for_every_variant(|v| {
+            if v.len() > 1 {
+                std::ops::ControlFlow::Break(v.len())
+            } else {
+                std::ops::ControlFlow::Continue(())
+            }
+        })
+        .unwrap_err();
+        let lengths: Vec<usize> = failures.into_iter().map(|failure| failure.error).collect();
+        assert_eq!(vec![2, 10], lengths);
+    }
+
+    #[test]
+    #[should_panic(expected = "checked")]
+    fn panics_are_passed_on() {
+        let _ = for_every_variant_on(2, |value: &Option<bool>| {
+            if *value == Some(false) {
+                panic!("checked");
+            }
+        });
+    }
+}
@@ -0,0 +1,59 @@
+//! Building block for walking cartesian products lazily
+
+/// Iterator returned by [`product`]
+pub struct Product<I, F, J> {
+    outer: I,
+    inner: Option<J>,
+    make_inner: F,
+}
+
+/// Like `Iterator::flat_map`, but only ever walks forwards. `flat_map` keeps both a front and
+/// a back inner iterator, which makes the size of nested products grow exponentially with
+/// the number of fields, while this one grows linearly.
+pub fn product<I, F, J>(outer: I, make_inner: F) -> Product<I::IntoIter, F, J::IntoIter>
+where
+    I: IntoIterator,
+    J: IntoIterator,
+    F: FnMut(I::Item) -> J,
+{
+    Product {
+        outer: outer.into_iter(),
+        inner: None,
+        make_inner,
+    }
+}
+
+impl<I, F, J> Iterator for Product<I, F, J::IntoIter>
+where
+    I: Iterator,
+    J: IntoIterator,
+    F: FnMut(I::Item) -> J,
+{
+    type Item = J::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.inner.as_mut().and_then(Iterator::next) {
+                return Some(item);
+            }
+            self.inner = Some((self.make_inner)(self.outer.next()?).into_iter());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::product;
+
+    #[test]
+    fn walks_every_combination() {
+        let pairs: Vec<(u8, char)> = product(0..3, |n| "ab".chars().map(move |c| (n, c))).collect();
+        assert_eq!(
+            vec![(0, 'a'), (0, 'b'), (1, 'a'), (1, 'b'), (2, 'a'), (2, 'b')],
+            pairs
+        );
+
+        let empty: Vec<(u8, u8)> = product(0..3, |n| (0..0).map(move |m| (n, m))).collect();
+        assert!(empty.is_empty());
+    }
+}
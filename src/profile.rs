@@ -0,0 +1,334 @@
+//! Profiles pick how many values are generated for a type, so one type definition serves both a
+//! quick test run and a thorough one.
+//!
+//! [`Minimal`] gives every enum variant at least once, [`Standard`] every variant as
+//! `every_variant` does, and [`Exhaustive`] adds the edge cases of the leaf types as well. The
+//! profile reaches every nested field: derived types and containers are built from the leaf
+//! types, which add their edge cases while inside [`exhaustively`]. So the exhaustive values
+//! are generated one at a time, counted, picked by index and sharded like the standard ones.
+//! A run can pick a profile through `EVERY_VARIANT_PROFILE`:
+//!
+//! ``` rust
+//! # use every_variant::*;
+//! #[derive(EveryVariant, Debug, Clone)]
+//! struct Reading {
+//!     sensor: Option<u8>,
+//! }
+//!
+//! assert_eq!(2, Reading::every_variant_in::<Standard>().len());
+//! assert!(Reading::every_variant_in::<Exhaustive>().len() > 2);
+//! // `EVERY_VARIANT_PROFILE=exhaustive cargo test` for the nightly run
+//! Reading::for_every_variant_from_env(|reading| println!("{:?}", reading));
+//! ```
+
+use crate::EveryVariant;
+use std::cell::Cell;
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+/// Environment variable naming the profile returned by [`ProfileKind::from_env`]
+pub const PROFILE_VARIABLE: &str = "EVERY_VARIANT_PROFILE";
+
+thread_local! {
+    /// Whether the leaf types add their edge cases to their variants right now
+    static EXHAUSTIVE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Whether values are generated for the [`Exhaustive`] profile right now, which the leaf types
+/// with [`EdgeCases`](crate::EdgeCases) check to add them to their variants
+pub fn is_exhaustive() -> bool {
+    EXHAUSTIVE.with(Cell::get)
+}
+
+/// Calls `f` with the leaf types adding their edge cases to their variants. Derived types and
+/// containers are built from the leaf types, so within `f` their `every_variant_iter`,
+/// `variant_count`, `nth_variant` and `variant_index` all work on the exhaustive values.
+pub fn exhaustively<R, F: FnOnce() -> R>(f: F) -> R {
+    let _scope = Scope::enter(true);
+    f()
+}
+
+/// Sets whether the leaf types add their edge cases, and sets it back when dropped, also when
+/// `f` panics
+struct Scope(bool);
+
+impl Scope {
+    fn enter(exhaustive: bool) -> Self {
+        Scope(EXHAUSTIVE.with(|current| current.replace(exhaustive)))
+    }
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        EXHAUSTIVE.with(|current| current.set(self.0));
+    }
+}
+
+/// An iterator generating each of its items [`exhaustively`], while what is done with them in
+/// between runs with the usual values
+pub struct ExhaustiveIter<I>(I);
+
+impl<I: Iterator> Iterator for ExhaustiveIter<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        exhaustively(|| self.0.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+/// A set of values to generate for every type, one of [`Minimal`], [`Standard`] and
+/// [`Exhaustive`]
+pub trait Profile {
+    /// The values of `T` in this profile, generated one at a time
+    fn variants_iter<T: EveryVariant>() -> impl Iterator<Item = T>;
+    /// The number of values of `T` in this profile
+    fn variant_count<T: EveryVariant>() -> usize;
+    /// The value of `T` at `index` in `variants_iter`, or `None` past the end
+    fn nth_variant<T: EveryVariant>(index: usize) -> Option<T>;
+
+    /// The values of `T` in this profile
+    fn variants<T: EveryVariant>() -> Vec<T> {
+        Self::variants_iter().collect()
+    }
+    /// Splits the values of `T` into `shards` contiguous ranges, and walks the one numbered
+    /// `shard` together with the index of each value
+    fn shard_variants<T: EveryVariant>(
+        shard: usize,
+        shards: usize,
+    ) -> impl Iterator<Item = (usize, T)> {
+        shard_range(Self::variant_count::<T>(), shard, shards)
+            .map_while(|index| Self::nth_variant(index).map(|value| (index, value)))
+    }
+}
+
+/// The indexes of the values in `shard` of `shards` contiguous ranges of `count` values
+fn shard_range(count: usize, shard: usize, shards: usize) -> Range<usize> {
+    let count = count as u128;
+    let shards = shards.max(1) as u128;
+    let start = (count * shard as u128 / shards) as usize;
+    let end = (count * (shard as u128 + 1) / shards).min(count) as usize;
+    start..end
+}
+
+/// Every enum variant at every level at least once, from `every_variant_shallow`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Minimal;
+
+/// Every combination of variants, from `every_variant`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Standard;
+
+/// Every combination of variants and edge cases, from `every_variant_exhaustive_iter`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Exhaustive;
+
+/// The shallow values are only generated together, so they are picked from all of them, and
+/// each shard generates them once instead of once for every value
+impl Profile for Minimal {
+    fn variants_iter<T: EveryVariant>() -> impl Iterator<Item = T> {
+        T::every_variant_shallow().into_iter()
+    }
+
+    fn variant_count<T: EveryVariant>() -> usize {
+        T::every_variant_shallow().len()
+    }
+
+    fn nth_variant<T: EveryVariant>(index: usize) -> Option<T> {
+        T::every_variant_shallow().into_iter().nth(index)
+    }
+
+    fn variants<T: EveryVariant>() -> Vec<T> {
+        T::every_variant_shallow()
+    }
+
+    fn shard_variants<T: EveryVariant>(
+        shard: usize,
+        shards: usize,
+    ) -> impl Iterator<Item = (usize, T)> {
+        let variants = T::every_variant_shallow();
+        let range = shard_range(variants.len(), shard, shards);
+        variants
+            .into_iter()
+            .enumerate()
+            .skip(range.start)
+            .take(range.len())
+    }
+}
+
+impl Profile for Standard {
+    fn variants_iter<T: EveryVariant>() -> impl Iterator<Item = T> {
+        T::every_variant_iter()
+    }
+
+    fn variant_count<T: EveryVariant>() -> usize {
+        T::variant_count()
+    }
+
+    fn nth_variant<T: EveryVariant>(index: usize) -> Option<T> {
+        T::nth_variant(index)
+    }
+}
+
+impl Profile for Exhaustive {
+    fn variants_iter<T: EveryVariant>() -> impl Iterator<Item = T> {
+        ExhaustiveIter(exhaustively(T::every_variant_iter))
+    }
+
+    fn variant_count<T: EveryVariant>() -> usize {
+        exhaustively(T::variant_count)
+    }
+
+    fn nth_variant<T: EveryVariant>(index: usize) -> Option<T> {
+        exhaustively(|| T::nth_variant(index))
+    }
+}
+
+/// One of the built in profiles, picked when the tests run instead of when they are written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ProfileKind {
+    Minimal,
+    #[default]
+    Standard,
+    Exhaustive,
+}
+
+impl ProfileKind {
+    /// The profile named in `EVERY_VARIANT_PROFILE`, or `Standard` when it is not set. Panics
+    /// on any other name, so a typo does not quietly run fewer values than asked for.
+    pub fn from_env() -> Self {
+        match std::env::var(PROFILE_VARIABLE) {
+            Ok(name) => name
+                .parse()
+                .unwrap_or_else(|err| panic!("{} in {}", err, PROFILE_VARIABLE)),
+            Err(_) => ProfileKind::Standard,
+        }
+    }
+
+    /// The values of `T` in this profile
+    pub fn variants<T: EveryVariant>(self) -> Vec<T> {
+        match self {
+            ProfileKind::Minimal => Minimal::variants(),
+            ProfileKind::Standard => Standard::variants(),
+            ProfileKind::Exhaustive => Exhaustive::variants(),
+        }
+    }
+
+    /// Calls the closure with each value of `T` in this profile in turn, generated one at a
+    /// time
+    pub fn for_every_variant<T: EveryVariant, F: FnMut(&T)>(self, closure: F) {
+        match self {
+            ProfileKind::Minimal => T::for_every_variant_in::<Minimal, F>(closure),
+            ProfileKind::Standard => T::for_every_variant_in::<Standard, F>(closure),
+            ProfileKind::Exhaustive => T::for_every_variant_in::<Exhaustive, F>(closure),
+        }
+    }
+}
+
+impl FromStr for ProfileKind {
+    type Err = UnknownProfile;
+
+    /// Accepts the names in any case, surrounding whitespace is ignored
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "minimal" => Ok(ProfileKind::Minimal),
+            "standard" => Ok(ProfileKind::Standard),
+            "exhaustive" => Ok(ProfileKind::Exhaustive),
+            _ => Err(UnknownProfile(name.to_string())),
+        }
+    }
+}
+
+/// A name that is not one of the built in profiles
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownProfile(pub String);
+
+impl fmt::Display for UnknownProfile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "unknown profile {:?}, expected minimal, standard or exhaustive",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for UnknownProfile {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_names() {
+        assert_eq!(Ok(ProfileKind::Minimal), "minimal".parse());
+        assert_eq!(Ok(ProfileKind::Exhaustive), " Exhaustive\n".parse());
+        assert_eq!(
+            Err(UnknownProfile("nightly".into())),
+            "nightly".parse::<ProfileKind>()
+        );
+        assert_eq!(ProfileKind::Standard, ProfileKind::default());
+    }
+
+    #[test]
+    fn profiles_grow() {
+        let minimal = ProfileKind::Minimal.variants::<(bool, Option<u8>)>();
+        let standard = ProfileKind::Standard.variants::<(bool, Option<u8>)>();
+        let exhaustive = ProfileKind::Exhaustive.variants::<(bool, Option<u8>)>();
+        assert_eq!(3, minimal.len());
+        assert_eq!(4, standard.len());
+        assert_eq!(
+            2 * (1 + u8::every_variant_exhaustive().len()),
+            exhaustive.len()
+        );
+        assert!(standard.iter().all(|value| exhaustive.contains(value)));
+    }
+
+    /// Counts how often its shallow values are generated
+    #[derive(Debug, Clone, PartialEq)]
+    struct Counted(u8);
+
+    thread_local! {
+        static SHALLOW_CALLS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    impl EveryVariant for Counted {
+        fn every_variant() -> Vec<Self> {
+            (0..6).map(Counted).collect()
+        }
+
+        fn every_variant_shallow() -> Vec<Self> {
+            SHALLOW_CALLS.with(|calls| calls.set(calls.get() + 1));
+            Self::every_variant()
+        }
+    }
+
+    #[test]
+    fn shards_generate_minimal_values_once() {
+        let values = Minimal::variants::<Counted>();
+        for shards in [1, 2, 5] {
+            let before = SHALLOW_CALLS.with(Cell::get);
+            let sharded: Vec<(usize, Counted)> = (0..shards)
+                .flat_map(|shard| Minimal::shard_variants(shard, shards))
+                .collect();
+            let calls = SHALLOW_CALLS.with(Cell::get) - before;
+
+            assert_eq!(
+                values,
+                sharded
+                    .iter()
+                    .map(|(_, value)| value.clone())
+                    .collect::<Vec<_>>()
+            );
+            assert!(sharded
+                .iter()
+                .enumerate()
+                .all(|(index, (at, _))| index == *at));
+            assert!(calls <= shards, "{} calls for {} shards", calls, shards);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//! Seeded random picking of variants, without any dependencies
+
+use crate::{outcome, EveryVariant};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Environment variable that fixes the seed returned by [`seed_from_env`]
+pub const SEED_VARIABLE: &str = "EVERY_VARIANT_SEED";
+
+/// Small pseudo random number generator (SplitMix64). The same seed always gives the same
+/// numbers, on every platform.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`, or 0 when `n` is 0
+    pub fn below(&mut self, n: usize) -> usize {
+        if n == 0 {
+            return 0;
+        }
+        // widening multiply, which is close enough to uniform for picking variants
+        ((self.next_u64() as u128 * n as u128) >> 64) as usize
+    }
+}
+
+/// The seed in `EVERY_VARIANT_SEED` if it is set, else one based on the current time. Print
+/// it along with failures, so the run can be repeated by setting the variable.
+pub fn seed_from_env() -> u64 {
+    std::env::var(SEED_VARIABLE)
+        .ok()
+        .and_then(|seed| seed.trim().parse().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|time| time.as_nanos() as u64)
+                .unwrap_or_default()
+        })
+}
+
+/// Calls the closure with `n` variants of `T` picked at random from `seed`. When it panics, it
+/// panics again naming the sample and `seed`, as described by `seed_note`.
+pub(crate) fn for_samples<T, F>(seed: u64, n: usize, mut closure: F, seed_note: &str)
+where
+    T: EveryVariant,
+    F: FnMut(&T),
+{
+    let mut rng = Rng::new(seed);
+    for sample in 0..n {
+        let value = match T::random_variant(&mut rng) {
+            Some(value) => value,
+            None => return,
+        };
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| closure(&value)));
+        if let Err(panic) = result {
+            panic!(
+                "sample {} of {} panicked {}: {}",
+                sample,
+                std::any::type_name::<T>(),
+                seed_note,
+                outcome::panic_message(&*panic)
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rng;
+
+    #[test]
+    fn same_seed_same_numbers() {
+        let first: Vec<u64> = (0..5)
+            .map({
+                let mut rng = Rng::new(42);
+                move |_| rng.next_u64()
+            })
+            .collect();
+        let second: Vec<u64> = (0..5)
+            .map({
+                let mut rng = Rng::new(42);
+                move |_| rng.next_u64()
+            })
+            .collect();
+        assert_eq!(first, second);
+        assert_ne!(first[0], Rng::new(43).next_u64());
+    }
+
+    #[test]
+    fn below_stays_in_range() {
+        let mut rng = Rng::new(7);
+        let mut seen = [false; 5];
+        for _ in 0..200 {
+            let n = rng.below(5);
+            assert!(n < 5);
+            seen[n] = true;
+        }
+        assert!(seen.iter().all(|&seen| seen));
+        assert_eq!(0, rng.below(0));
+    }
+}
@@ -1,12 +1,87 @@
+use crate::covering;
+use crate::product::product;
 use crate::*;
 
+use std::any::{Any, TypeId};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, VecDeque};
+use std::convert::{TryFrom, TryInto};
+use std::hash::{BuildHasher, Hash};
+use std::iter;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex, OnceLock};
+
 // ======================= Implementations ===================
 //
 // These are some kind of dumb implementations for "every variant" that can be basic structs
 // There are infinite numbers of variants for vectors and the like, this will just give a small
 // taste
 
+macro_rules! count {
+    () => { 0 };
+    ($head: expr $(, $tail: expr)*) => { 1 + count!($($tail),*) };
+}
+
+/// The variants of a leaf type with edge cases, built once for each profile instead of on every
+/// call, as some of them, like the long string, are expensive to build
+trait CachedVariants: Sized + 'static {
+    fn cached_variants() -> &'static [Self];
+}
+
 macro_rules! std_impl {
+    // floats are found by their bits, so NaN is found as well, and `-0.0` is told apart from
+    // `0.0`
+    (floats $ty: ty, $($vals: expr),+) => {
+        std_impl!(edge_cases $ty, $($vals),+; |variant: &$ty, value: &$ty| {
+            variant.to_bits() == value.to_bits()
+        });
+    };
+    // leaf types that add their `EdgeCases` to their variants in the exhaustive profile
+    (edge_cases $ty: ty, $($vals: expr),+) => {
+        std_impl!(edge_cases $ty, $($vals),+; PartialEq::eq);
+    };
+    (edge_cases $ty: ty, $($vals: expr),+; $same: expr) => {
+        impl CachedVariants for $ty {
+            fn cached_variants() -> &'static [Self] {
+                static VARIANTS: OnceLock<Vec<$ty>> = OnceLock::new();
+                static EXHAUSTIVE: OnceLock<Vec<$ty>> = OnceLock::new();
+                let variants = || vec![
+                $(
+                    $vals,
+                )+
+                ];
+                if profile::is_exhaustive() {
+                    EXHAUSTIVE.get_or_init(|| edge_cases::with_edge_cases(variants()))
+                } else {
+                    VARIANTS.get_or_init(variants)
+                }
+            }
+        }
+
+        impl EveryVariant for $ty {
+            fn every_variant() -> Vec<Self> {
+                Self::cached_variants().to_vec()
+            }
+
+            fn every_variant_iter() -> impl Iterator<Item = Self> {
+                Self::cached_variants().iter().cloned()
+            }
+
+            fn variant_count() -> usize {
+                Self::cached_variants().len()
+            }
+
+            fn nth_variant(index: usize) -> Option<Self> {
+                Self::cached_variants().get(index).cloned()
+            }
+
+            fn variant_index(&self) -> Option<usize> {
+                Self::cached_variants()
+                    .iter()
+                    .position(|variant| ($same)(variant, self))
+            }
+        }
+    };
     ($ty: ty, $($vals: expr),+) => {
         impl EveryVariant for $ty {
             fn every_variant() -> Vec<Self> {
@@ -17,358 +92,2727 @@ macro_rules! std_impl {
                 ];
                 vec
             }
+
+            fn every_variant_iter() -> impl Iterator<Item = Self> {
+                IntoIterator::into_iter([
+                $(
+                    $vals,
+                )+
+                ])
+            }
+
+            fn variant_count() -> usize {
+                count!($($vals),+)
+            }
+
+            fn variant_index(&self) -> Option<usize> {
+                position_of(self)
+            }
         }
     };
 }
 
 std_impl!((), ());
 
-std_impl!(String, "example String".into());
-std_impl!(&'static str, "&ŝtatic str!");
+std_impl!(edge_cases String, "example String".into());
+std_impl!(edge_cases &'static str, "&ŝtatic str!");
 
-std_impl!(u8, 8);
-std_impl!(u16, 16);
-std_impl!(u32, 32);
-std_impl!(u64, 64);
-std_impl!(u128, 128);
+std_impl!(edge_cases u8, 8);
+std_impl!(edge_cases u16, 16);
+std_impl!(edge_cases u32, 32);
+std_impl!(edge_cases u64, 64);
+std_impl!(edge_cases u128, 128);
 
-std_impl!(i8, -8);
-std_impl!(i16, -16);
-std_impl!(i32, -32);
-std_impl!(i64, -64);
-std_impl!(i128, -128);
+std_impl!(edge_cases i8, -8);
+std_impl!(edge_cases i16, -16);
+std_impl!(edge_cases i32, -32);
+std_impl!(edge_cases i64, -64);
+std_impl!(edge_cases i128, -128);
 
-std_impl!(f32, 32., -32.);
-std_impl!(f64, 32., -32.);
+std_impl!(floats f32, 32., -32.);
+std_impl!(floats f64, 32., -32.);
 
-std_impl!(usize, 0, usize::MAX);
+std_impl!(edge_cases usize, 0, usize::MAX);
 std_impl!(bool, true, false);
 
-std_impl!(std::num::NonZeroU8, Self::new(8).unwrap());
-std_impl!(std::num::NonZeroU16, Self::new(16).unwrap());
-std_impl!(std::num::NonZeroU32, Self::new(32).unwrap());
-std_impl!(std::num::NonZeroU64, Self::new(64).unwrap());
-std_impl!(std::num::NonZeroU128, Self::new(128).unwrap());
+std_impl!(edge_cases std::num::NonZeroU8, Self::new(8).unwrap());
+std_impl!(edge_cases std::num::NonZeroU16, Self::new(16).unwrap());
+std_impl!(edge_cases std::num::NonZeroU32, Self::new(32).unwrap());
+std_impl!(edge_cases std::num::NonZeroU64, Self::new(64).unwrap());
+std_impl!(edge_cases std::num::NonZeroU128, Self::new(128).unwrap());
 
 std_impl!(
-    std::num::NonZeroI8,
+    edge_cases std::num::NonZeroI8,
     Self::new(8).unwrap(),
     Self::new(-8).unwrap()
 );
 std_impl!(
-    std::num::NonZeroI16,
+    edge_cases std::num::NonZeroI16,
     Self::new(16).unwrap(),
     Self::new(-16).unwrap()
 );
 std_impl!(
-    std::num::NonZeroI32,
+    edge_cases std::num::NonZeroI32,
     Self::new(32).unwrap(),
     Self::new(-32).unwrap()
 );
 std_impl!(
-    std::num::NonZeroI64,
+    edge_cases std::num::NonZeroI64,
     Self::new(64).unwrap(),
     Self::new(-64).unwrap()
 );
 std_impl!(
-    std::num::NonZeroI128,
+    edge_cases std::num::NonZeroI128,
     Self::new(128).unwrap(),
     Self::new(-128).unwrap()
 );
 
 impl<T: EveryVariant + Clone + Sized> EveryVariant for Option<T> {
     fn every_variant() -> Vec<Self> {
-        let mut vec = Vec::new();
-        vec.push(None);
-
-        let tvec = T::every_variant();
-        let mut tvec = tvec.into_iter().map(|t| Some(t)).collect();
-        vec.append(&mut tvec);
-
-        vec
+        Self::every_variant_iter().collect()
     }
-}
 
-impl<T: EveryVariant + Clone + Sized, E: EveryVariant + Clone + Sized> EveryVariant
-    for Result<T, E>
-{
-    fn every_variant() -> Vec<Self> {
-        let mut vec = Vec::new();
+    fn every_variant_iter() -> impl Iterator<Item = Self> {
+        iter::once(None).chain(T::every_variant_iter().map(Some))
+    }
 
-        let tvec = T::every_variant();
-        let mut tvec = tvec.into_iter().map(|t| Ok(t)).collect();
-        vec.append(&mut tvec);
+    fn variant_count() -> usize {
+        T::variant_count().saturating_add(1)
+    }
 
-        let evec = E::every_variant();
-        let mut evec = evec.into_iter().map(|t| Err(t)).collect();
-        vec.append(&mut evec);
+    fn every_variant_shallow() -> Vec<Self> {
+        iter::once(None)
+            .chain(T::every_variant_shallow().into_iter().map(Some))
+            .collect()
+    }
 
-        vec
+    fn nth_variant(index: usize) -> Option<Self> {
+        match index {
+            0 => Some(None),
+            _ => T::nth_variant(index - 1).map(Some),
+        }
     }
-}
 
-impl<T: EveryVariant + Clone + Sized> EveryVariant for Vec<T> {
-    fn every_variant() -> Vec<Self> {
-        // vector that contains one of every variant of the subsequent types
-        let mut retvec = vec![T::every_variant()];
+    fn variant_index(&self) -> Option<usize> {
+        match self {
+            None => Some(0),
+            Some(value) => value.variant_index()?.checked_add(1),
+        }
+    }
 
-        // add an empty vector
-        retvec.push(Vec::new());
+    fn variant_paths(prefix: &str, paths: &mut Vec<String>) {
+        paths.push(format!("{}Option::None", prefix));
+        if T::variant_count() != 0 {
+            paths.push(format!("{}Option::Some", prefix));
+            T::variant_paths(&format!("{}Option::Some.0.", prefix), paths);
+        }
+    }
 
-        match T::every_variant().first().cloned() {
+    fn observed_variant_paths(&self, prefix: &str, paths: &mut Vec<String>) {
+        match self {
+            None => paths.push(format!("{}Option::None", prefix)),
             Some(value) => {
-                // add a single element vector
-                let singlevec = vec![value.clone()];
-                retvec.push(singlevec);
-
-                // add a multi-element vector
-                let mut largevec = Vec::new();
-                while largevec.len() < 10 {
-                    largevec.push(value.clone());
-                }
-                retvec.push(largevec);
+                paths.push(format!("{}Option::Some", prefix));
+                value.observed_variant_paths(&format!("{}Option::Some.0.", prefix), paths);
             }
-            None => {}
-        };
-
-        retvec
+        }
     }
-}
-
-#[cfg(feature = "ev_heapless")]
-use heapless::{String as HString, Vec as HVec};
 
-#[cfg(feature = "ev_heapless")]
-impl<T, const N: usize> EveryVariant for HVec<T, N>
-where
-    T: EveryVariant + Clone + Sized,
-{
-    fn every_variant() -> Vec<Self> {
-        let mut vec = HVec::new();
-
-        for v in T::every_variant() {
-            vec.push(v).ok();
+    fn random_variant(rng: &mut Rng) -> Option<Self> {
+        // like derived enums, None and Some are equally likely
+        if T::variant_count() == 0 || rng.below(2) == 0 {
+            Some(None)
+        } else {
+            T::random_variant(rng).map(Some)
         }
+    }
 
-        vec![vec]
+    fn covering_variants(strength: usize) -> (Vec<Self>, Coverage) {
+        let (values, coverage) = T::covering_variants(strength);
+        let vec = iter::once(None)
+            .chain(values.into_iter().map(Some))
+            .collect();
+
+        let mut coverage = coverage + Coverage::complete(strength, 1);
+        coverage.full_product = Self::variant_count();
+        (vec, coverage)
     }
 }
 
-#[cfg(feature = "ev_heapless")]
-impl<const N: usize> EveryVariant for HString<N> {
+impl<T: EveryVariant + Clone + Sized, E: EveryVariant + Clone + Sized> EveryVariant
+    for Result<T, E>
+{
     fn every_variant() -> Vec<Self> {
-        let mut s = HString::new();
-        s.push_str("hello").ok();
-        vec![s]
+        Self::every_variant_iter().collect()
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use crate::EveryVariant;
-    #[cfg(feature = "ev_heapless")]
-    use heapless::{String as HString, Vec as HVec};
 
-    /// Type of the message
-    #[derive(EveryVariant, Debug, Clone)]
-    enum MessageType {
-        Codified,
-        Markdown,
-        Html,
+    fn every_variant_iter() -> impl Iterator<Item = Self> {
+        T::every_variant_iter()
+            .map(Ok)
+            .chain(E::every_variant_iter().map(Err))
     }
 
-    /// This type can come in  4 different variants due the option
-    #[derive(EveryVariant, Debug, Clone)]
-    struct FormattedMessage {
-        /// Enum dictating how to render the string, None means its hidden
-        rendermethod: Option<MessageType>,
-        /// The optional content of the message
-        text: String,
+    fn variant_count() -> usize {
+        T::variant_count().saturating_add(E::variant_count())
     }
 
-    #[test]
-    fn small_example() {
-        let all_diferent_messages = FormattedMessage::every_variant();
-        println!("{:#?}", all_diferent_messages);
+    fn every_variant_shallow() -> Vec<Self> {
+        T::every_variant_shallow()
+            .into_iter()
+            .map(Ok)
+            .chain(E::every_variant_shallow().into_iter().map(Err))
+            .collect()
+    }
 
-        let opt_msg_len = Option::<MessageType>::every_variant().len();
-        let text_len = String::every_variant().len();
+    fn nth_variant(index: usize) -> Option<Self> {
+        let oks = T::variant_count();
+        if index < oks {
+            T::nth_variant(index).map(Ok)
+        } else {
+            E::nth_variant(index - oks).map(Err)
+        }
+    }
 
-        assert_eq!(opt_msg_len * text_len, all_diferent_messages.len());
+    fn variant_index(&self) -> Option<usize> {
+        match self {
+            Ok(value) => value.variant_index(),
+            Err(value) => value.variant_index()?.checked_add(T::variant_count()),
+        }
     }
 
-    #[cfg(feature = "ev_heapless")]
-    #[test]
-    fn heapless() {
-        let _s = HString::<16>::every_variant();
-        let _v = HVec::<u8, 16>::every_variant();
+    fn variant_paths(prefix: &str, paths: &mut Vec<String>) {
+        // like derived enums, variants without values are left out
+        if T::variant_count() != 0 {
+            paths.push(format!("{}Result::Ok", prefix));
+            T::variant_paths(&format!("{}Result::Ok.0.", prefix), paths);
+        }
+        if E::variant_count() != 0 {
+            paths.push(format!("{}Result::Err", prefix));
+            E::variant_paths(&format!("{}Result::Err.0.", prefix), paths);
+        }
     }
 
-    #[derive(EveryVariant, Debug, Clone)]
-    pub struct Message {
-        pub message: String,
-        pub number: u32,
-        pub opt: Option<u64>,
-        pub nest: Top,
-        pub second: SecondTop,
+    fn observed_variant_paths(&self, prefix: &str, paths: &mut Vec<String>) {
+        match self {
+            Ok(value) => {
+                paths.push(format!("{}Result::Ok", prefix));
+                value.observed_variant_paths(&format!("{}Result::Ok.0.", prefix), paths);
+            }
+            Err(value) => {
+                paths.push(format!("{}Result::Err", prefix));
+                value.observed_variant_paths(&format!("{}Result::Err.0.", prefix), paths);
+            }
+        }
     }
 
-    #[derive(EveryVariant, Debug, Clone)]
-    pub enum SecondTop {
-        One,
-        Two(Nested),
-        Three,
+    fn random_variant(rng: &mut Rng) -> Option<Self> {
+        let ok = T::variant_count() != 0;
+        let err = E::variant_count() != 0;
+        match (ok, err) {
+            (true, true) if rng.below(2) == 0 => T::random_variant(rng).map(Ok),
+            (true, true) => E::random_variant(rng).map(Err),
+            (true, false) => T::random_variant(rng).map(Ok),
+            (false, true) => E::random_variant(rng).map(Err),
+            (false, false) => None,
+        }
     }
 
-    #[derive(EveryVariant, Debug, Clone)]
-    pub enum Top {
-        One,
-        Nested(Nested),
+    fn covering_variants(strength: usize) -> (Vec<Self>, Coverage) {
+        let (oks, ok_coverage) = T::covering_variants(strength);
+        let (errs, err_coverage) = E::covering_variants(strength);
+        let vec = oks
+            .into_iter()
+            .map(Ok)
+            .chain(errs.into_iter().map(Err))
+            .collect();
+
+        let mut coverage = ok_coverage + err_coverage;
+        coverage.full_product = Self::variant_count();
+        (vec, coverage)
     }
+}
 
-    #[derive(EveryVariant, Debug, Clone)]
-    pub enum Nested {
-        First,
-        Second,
-        Third,
+impl<T: EveryVariant + Clone + Sized> EveryVariant for Vec<T> {
+    fn every_variant() -> Vec<Self> {
+        Self::every_variant_iter().collect()
     }
 
-    #[derive(EveryVariant, Debug, Clone)]
-    pub struct TestUnnamed3(pub u16);
+    fn every_variant_iter() -> impl Iterator<Item = Self> {
+        // vector that contains one of every variant of the subsequent types, unless there are
+        // none and it would be the same as the empty one
+        let every = iter::once_with(|| depth::nested::<T, _, _>(T::every_variant))
+            .flatten()
+            .filter(|every| !every.is_empty());
 
-    #[test]
-    fn messages_number() {
-        let msgs = Message::every_variant().len();
-        let messages_len = String::every_variant().len();
-        let number_len = u32::every_variant().len();
-        let opt_len = Option::<u64>::every_variant().len();
-        let nest_len = Top::every_variant().len();
-        let second_len = SecondTop::every_variant().len();
+        // add an empty vector
+        let empty = iter::once(Vec::new());
 
-        assert_eq!(
-            (messages_len * number_len * opt_len * nest_len * second_len),
-            msgs
-        );
+        // add a single element vector and a multi-element vector
+        let first = iter::once_with(|| {
+            depth::nested::<T, _, _>(|| T::every_variant_iter().next()).flatten()
+        })
+        .flatten()
+        .flat_map(|value| IntoIterator::into_iter([vec![value.clone()], vec![value; 10]]));
+
+        every.chain(empty).chain(first)
     }
 
-    #[test]
-    fn opts_number() {
-        let msgs = Option::<u64>::every_variant().len();
-        assert_eq!(u64::every_variant().len() + 1, msgs);
+    fn variant_count() -> usize {
+        match depth::nested::<T, _, _>(T::variant_count).unwrap_or(0) {
+            0 => 1,
+            _ => 4,
+        }
     }
 
-    #[derive(EveryVariant, Debug, Clone)]
-    pub enum TestUnnamed1 {
-        UnnamedSingle(u16),
-        UnnamedMultiple1(u16, u32),
-        UnnamedMultiple2(u16, u32, u64, i32),
+    fn every_variant_shallow() -> Vec<Self> {
+        // same as every_variant, with the shallow values in the first vector
+        vec_variants(depth::nested::<T, _, _>(T::every_variant_shallow).unwrap_or_default())
     }
 
-    #[test]
-    fn unnamed1() {
-        let msgs = TestUnnamed1::every_variant().len();
-        let u16_len = u16::every_variant().len();
-        let u32_len = u32::every_variant().len();
-        let u64_len = u64::every_variant().len();
-        let i32_len = i32::every_variant().len();
-        assert_eq!(
-            u16_len + u16_len * u32_len + u16_len * u32_len * u64_len * i32_len,
-            msgs
-        );
+    fn variant_index(&self) -> Option<usize> {
+        let indexes = element_indexes(self.iter())?;
+        Self::every_variant_iter()
+            .position(|vec| element_indexes(vec.iter()).as_ref() == Some(&indexes))
     }
 
-    #[derive(EveryVariant, Debug, Clone)]
-    pub struct TestUnnamed2(u16, u32, u64);
+    // the elements are all at the same path
+    fn variant_paths(prefix: &str, paths: &mut Vec<String>) {
+        depth::nested::<T, _, _>(|| T::variant_paths(prefix, paths));
+    }
 
-    #[test]
-    fn unnamed2() {
-        let msgs = TestUnnamed2::every_variant().len();
-        let u16_len = u16::every_variant().len();
-        let u32_len = u32::every_variant().len();
-        let u64_len = u64::every_variant().len();
-        assert_eq!(u16_len * u32_len * u64_len, msgs);
+    fn observed_variant_paths(&self, prefix: &str, paths: &mut Vec<String>) {
+        for value in self {
+            value.observed_variant_paths(prefix, paths);
+        }
     }
+}
 
-    #[derive(EveryVariant, Debug, Clone)]
-    pub struct Gen1<A: EveryVariant + Clone>(A);
+/// The indexes of the elements, one level deeper into `T`, or `None` when one of them has none.
+/// Containers find their own index by comparing these with the ones of their variants, as
+/// their variants can repeat when those of `T` do, like `vec![()]` in `Vec<()>`.
+fn element_indexes<'a, T, I>(elements: I) -> Option<Vec<usize>>
+where
+    T: EveryVariant + 'a,
+    I: IntoIterator<Item = &'a T>,
+{
+    let mut elements = elements.into_iter().peekable();
+    if elements.peek().is_none() {
+        // also when too deep for `T`
+        return Some(Vec::new());
+    }
+    depth::nested::<T, _, _>(|| elements.map(EveryVariant::variant_index).collect()).flatten()
+}
 
-    #[derive(EveryVariant, Debug, Clone)]
-    pub struct Gen2<A: EveryVariant + Clone, B: EveryVariant + Clone>(A, B);
+/// The element indexes of a set in order, as sets are compared without their order
+fn set_indexes<'a, T, I>(elements: I) -> Option<Vec<usize>>
+where
+    T: EveryVariant + 'a,
+    I: IntoIterator<Item = &'a T>,
+{
+    let mut indexes = element_indexes(elements)?;
+    indexes.sort_unstable();
+    Some(indexes)
+}
 
-    #[derive(EveryVariant, Debug, Clone)]
-    pub struct Generic1(Gen1<u8>, Gen2<u16, u32>);
-    #[test]
-    fn generic1() {
-        let msgs = Generic1::every_variant().len();
+/// The vectors `every_variant` gives for `Vec<T>`, from the values in `every` in place of the
+/// variants of `T`
+fn vec_variants<T: Clone>(every: Vec<T>) -> Vec<Vec<T>> {
+    let first = match every.first() {
+        Some(first) => first.clone(),
+        None => return vec![Vec::new()],
+    };
+    vec![every, Vec::new(), vec![first.clone()], vec![first; 10]]
+}
 
-        let gen1_len = Gen1::<u8>::every_variant().len();
-        let gen2_len = Gen2::<u16, u32>::every_variant().len();
+// Sets are the same as vectors, without the vector repeating the first variant as that is a
+// single element again: one with every variant, an empty one and one with the first variant.
+// `Vec`s of the elements are passed around, to share this between the set types.
 
-        assert_eq!(gen1_len * gen2_len, msgs);
+fn set_variants<T: Clone>(every: Vec<T>) -> Vec<Vec<T>> {
+    let mut sets = Vec::new();
+    if !every.is_empty() {
+        sets.push(every.clone());
     }
-
-    #[derive(EveryVariant, Debug, Clone)]
-    pub enum Generic2 {
-        G1(Gen1<i8>),
-        G2(Gen2<i16, i32>),
+    sets.push(Vec::new());
+    if every.len() > 1 {
+        sets.push(vec![every[0].clone()]);
     }
+    sets
+}
 
-    #[test]
-    fn generic2() {
-        let msgs = Generic2::every_variant().len();
-        let gen1_len = Gen1::<i8>::every_variant().len();
-        let gen2_len = Gen2::<i16, i32>::every_variant().len();
-        assert_eq!(gen1_len + gen2_len, msgs);
+fn set_count<T: EveryVariant>() -> usize {
+    match depth::nested::<T, _, _>(T::variant_count).unwrap_or(0) {
+        0 => 1,
+        1 => 2,
+        _ => 3,
     }
+}
 
-    #[derive(EveryVariant, Debug, Clone)]
-    pub enum TestNamed1 {
-        NamedSingle { first: u16 },
-        NamedMultiple1 { first: u16, second: u32 },
-        NamedMultiple2 { first: u16, second: u32, third: u64 },
+// Maps are empty, have a single entry for every variant of the values, the keys taking turns,
+// or an entry for every variant of the keys, the values taking turns. The keys of an entry are
+// always different, so lookups do not collide.
+
+fn map_variants<K: Clone, V: Clone>(keys: Vec<K>, values: Vec<V>) -> Vec<Vec<(K, V)>> {
+    let mut maps = vec![Vec::new()];
+    if keys.is_empty() || values.is_empty() {
+        return maps;
     }
 
-    #[test]
-    fn named_enum() {
-        let msgs = TestNamed1::every_variant().len();
-        let u16_len = u16::every_variant().len();
-        let u32_len = u32::every_variant().len();
-        let u64_len = u64::every_variant().len();
-        assert_eq!(
-            u16_len + u16_len * u32_len + u16_len * u32_len * u64_len,
-            msgs
-        );
+    for (idx, value) in values.iter().enumerate() {
+        maps.push(vec![(keys[idx % keys.len()].clone(), value.clone())]);
     }
+    if keys.len() > 1 {
+        let entries = keys
+            .iter()
+            .enumerate()
+            .map(|(idx, key)| (key.clone(), values[idx % values.len()].clone()));
+        maps.push(entries.collect());
+    }
+    maps
+}
 
-    #[allow(unused)]
-    #[derive(EveryVariant)]
-    pub struct GenericDerive<T> {
-        value: T,
+fn map_counts<K: EveryVariant, V: EveryVariant>() -> (usize, usize) {
+    (
+        depth::nested::<K, _, _>(K::variant_count).unwrap_or(0),
+        depth::nested::<V, _, _>(V::variant_count).unwrap_or(0),
+    )
+}
+
+fn map_count<K: EveryVariant, V: EveryVariant>() -> usize {
+    match map_counts::<K, V>() {
+        (0, _) | (_, 0) => 1,
+        (1, values) => values.saturating_add(1),
+        (_, values) => values.saturating_add(2),
     }
+}
 
-    #[allow(unused)]
+/// The key and value indexes of the entries of a map in order
+fn entry_indexes<'a, K, V, I>(map: I) -> Option<Vec<(usize, usize)>>
+where
+    K: EveryVariant + 'a,
+    V: EveryVariant + 'a,
+    I: Iterator<Item = (&'a K, &'a V)>,
+{
+    let mut entries = Vec::new();
+    for (key, value) in map {
+        let key = depth::nested::<K, _, _>(|| key.variant_index()).flatten()?;
+        let value = depth::nested::<V, _, _>(|| value.variant_index()).flatten()?;
+        entries.push((key, value));
+    }
+    entries.sort_unstable();
+    Some(entries)
+}
+
+fn map_paths<K: EveryVariant, V: EveryVariant>(prefix: &str, paths: &mut Vec<String>) {
+    depth::nested::<K, _, _>(|| K::variant_paths(&format!("{}keys.", prefix), paths));
+    depth::nested::<V, _, _>(|| V::variant_paths(&format!("{}values.", prefix), paths));
+}
+
+fn observed_map_paths<'a, K, V, I>(map: I, prefix: &str, paths: &mut Vec<String>)
+where
+    K: EveryVariant + 'a,
+    V: EveryVariant + 'a,
+    I: Iterator<Item = (&'a K, &'a V)>,
+{
+    let keys = format!("{}keys.", prefix);
+    let values = format!("{}values.", prefix);
+    for (key, value) in map {
+        key.observed_variant_paths(&keys, paths);
+        value.observed_variant_paths(&values, paths);
+    }
+}
+
+// The bodies name the element types `T`, or `K` and `V` for maps, which the impls pass in
+macro_rules! set_impl {
+    (impl<$($param: ident),+> $set: ty where $($bounds: tt)+) => {
+        impl<$($param),+> EveryVariant for $set where $($bounds)+ {
+            fn every_variant() -> Vec<Self> {
+                let every = depth::nested::<T, _, _>(T::every_variant).unwrap_or_default();
+                set_variants(every)
+                    .into_iter()
+                    .map(|set| set.into_iter().collect())
+                    .collect()
+            }
+
+            fn variant_count() -> usize {
+                set_count::<T>()
+            }
+
+            fn every_variant_shallow() -> Vec<Self> {
+                let every = depth::nested::<T, _, _>(T::every_variant_shallow).unwrap_or_default();
+                set_variants(every)
+                    .into_iter()
+                    .map(|set| set.into_iter().collect())
+                    .collect()
+            }
+
+            fn variant_index(&self) -> Option<usize> {
+                let indexes = set_indexes(self.iter())?;
+                Self::every_variant()
+                    .iter()
+                    .position(|set| set_indexes(set.iter()).as_ref() == Some(&indexes))
+            }
+
+            fn variant_paths(prefix: &str, paths: &mut Vec<String>) {
+                depth::nested::<T, _, _>(|| T::variant_paths(prefix, paths));
+            }
+
+            fn observed_variant_paths(&self, prefix: &str, paths: &mut Vec<String>) {
+                for value in self.iter() {
+                    value.observed_variant_paths(prefix, paths);
+                }
+            }
+        }
+    };
+}
+
+set_impl!(impl<T> BTreeSet<T> where T: EveryVariant + Clone + Ord);
+set_impl!(
+    impl<T, S> HashSet<T, S>
+    where
+        T: EveryVariant + Clone + Eq + Hash,
+        S: BuildHasher + Default
+);
+
+macro_rules! map_impl {
+    (impl<$($param: ident),+> $map: ty where $($bounds: tt)+) => {
+        impl<$($param),+> EveryVariant for $map where $($bounds)+ {
+            fn every_variant() -> Vec<Self> {
+                let keys = depth::nested::<K, _, _>(K::every_variant).unwrap_or_default();
+                let values = depth::nested::<V, _, _>(V::every_variant).unwrap_or_default();
+                map_variants(keys, values)
+                    .into_iter()
+                    .map(|map| map.into_iter().collect())
+                    .collect()
+            }
+
+            fn variant_count() -> usize {
+                map_count::<K, V>()
+            }
+
+            fn every_variant_shallow() -> Vec<Self> {
+                let keys = depth::nested::<K, _, _>(K::every_variant_shallow).unwrap_or_default();
+                let values = depth::nested::<V, _, _>(V::every_variant_shallow).unwrap_or_default();
+                map_variants(keys, values)
+                    .into_iter()
+                    .map(|map| map.into_iter().collect())
+                    .collect()
+            }
+
+            fn variant_index(&self) -> Option<usize> {
+                let entries = entry_indexes(self.iter())?;
+                Self::every_variant()
+                    .iter()
+                    .position(|map| entry_indexes(map.iter()).as_ref() == Some(&entries))
+            }
+
+            fn variant_paths(prefix: &str, paths: &mut Vec<String>) {
+                map_paths::<K, V>(prefix, paths);
+            }
+
+            fn observed_variant_paths(&self, prefix: &str, paths: &mut Vec<String>) {
+                observed_map_paths(self.iter(), prefix, paths);
+            }
+        }
+    };
+}
+
+map_impl!(
+    impl<K, V> BTreeMap<K, V>
+    where
+        K: EveryVariant + Clone + Ord,
+        V: EveryVariant + Clone
+);
+map_impl!(
+    impl<K, V, S> HashMap<K, V, S>
+    where
+        K: EveryVariant + Clone + Eq + Hash,
+        V: EveryVariant + Clone,
+        S: BuildHasher + Default
+);
+
+/// The same variants as `Vec<T>`
+impl<T: EveryVariant + Clone> EveryVariant for VecDeque<T> {
+    fn every_variant() -> Vec<Self> {
+        Self::every_variant_iter().collect()
+    }
+
+    fn every_variant_iter() -> impl Iterator<Item = Self> {
+        Vec::<T>::every_variant_iter().map(VecDeque::from)
+    }
+
+    fn variant_count() -> usize {
+        Vec::<T>::variant_count()
+    }
+
+    fn every_variant_shallow() -> Vec<Self> {
+        Vec::<T>::every_variant_shallow()
+            .into_iter()
+            .map(VecDeque::from)
+            .collect()
+    }
+
+    fn variant_index(&self) -> Option<usize> {
+        self.iter().cloned().collect::<Vec<T>>().variant_index()
+    }
+
+    fn variant_paths(prefix: &str, paths: &mut Vec<String>) {
+        Vec::<T>::variant_paths(prefix, paths);
+    }
+
+    fn observed_variant_paths(&self, prefix: &str, paths: &mut Vec<String>) {
+        for value in self {
+            value.observed_variant_paths(prefix, paths);
+        }
+    }
+}
+
+/// The same variants as `Vec<T>`, the heap only keeps the order of the largest element
+impl<T: EveryVariant + Clone + Ord> EveryVariant for BinaryHeap<T> {
+    fn every_variant() -> Vec<Self> {
+        Self::every_variant_iter().collect()
+    }
+
+    fn every_variant_iter() -> impl Iterator<Item = Self> {
+        Vec::<T>::every_variant_iter().map(BinaryHeap::from)
+    }
+
+    fn variant_count() -> usize {
+        Vec::<T>::variant_count()
+    }
+
+    fn every_variant_shallow() -> Vec<Self> {
+        Vec::<T>::every_variant_shallow()
+            .into_iter()
+            .map(BinaryHeap::from)
+            .collect()
+    }
+
+    fn variant_index(&self) -> Option<usize> {
+        // the elements are compared by their own index, like those of `Vec<T>`
+        let sorted = element_indexes(self.clone().into_sorted_vec().iter())?;
+        Self::every_variant_iter().position(|heap| {
+            element_indexes(heap.into_sorted_vec().iter()).as_ref() == Some(&sorted)
+        })
+    }
+
+    fn variant_paths(prefix: &str, paths: &mut Vec<String>) {
+        Vec::<T>::variant_paths(prefix, paths);
+    }
+
+    fn observed_variant_paths(&self, prefix: &str, paths: &mut Vec<String>) {
+        for value in self {
+            value.observed_variant_paths(prefix, paths);
+        }
+    }
+}
+
+// Boxes and the other pointers are where types usually recurse, the variants of `T` are
+// generated one level deeper into it, see `depth`. They are collected into a vector instead of
+// generated lazily, as `T` can contain pointers to itself, which would make the iterator type
+// infinitely large.
+macro_rules! pointer_impl {
+    ($($ptr: ident),+) => {
+        $(
+            impl<T: EveryVariant + Clone> EveryVariant for $ptr<T> {
+                fn every_variant() -> Vec<Self> {
+                    depth::nested::<T, _, _>(T::every_variant)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map($ptr::new)
+                        .collect()
+                }
+
+                fn variant_count() -> usize {
+                    depth::nested::<T, _, _>(T::variant_count).unwrap_or(0)
+                }
+
+                fn every_variant_shallow() -> Vec<Self> {
+                    depth::nested::<T, _, _>(T::every_variant_shallow)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map($ptr::new)
+                        .collect()
+                }
+
+                fn nth_variant(index: usize) -> Option<Self> {
+                    depth::nested::<T, _, _>(|| T::nth_variant(index))
+                        .flatten()
+                        .map($ptr::new)
+                }
+
+                fn variant_index(&self) -> Option<usize> {
+                    depth::nested::<T, _, _>(|| (**self).variant_index()).flatten()
+                }
+
+                fn variant_paths(prefix: &str, paths: &mut Vec<String>) {
+                    depth::nested::<T, _, _>(|| T::variant_paths(prefix, paths));
+                }
+
+                fn observed_variant_paths(&self, prefix: &str, paths: &mut Vec<String>) {
+                    (**self).observed_variant_paths(prefix, paths);
+                }
+
+                fn random_variant(rng: &mut Rng) -> Option<Self> {
+                    depth::nested::<T, _, _>(|| T::random_variant(rng))
+                        .flatten()
+                        .map($ptr::new)
+                }
+
+                fn covering_variants(strength: usize) -> (Vec<Self>, Coverage) {
+                    let (values, coverage) =
+                        depth::nested::<T, _, _>(|| T::covering_variants(strength))
+                            .unwrap_or_else(|| (Vec::new(), Coverage::complete(strength, 0)));
+                    (values.into_iter().map($ptr::new).collect(), coverage)
+                }
+            }
+
+            /// The same values as `String`
+            impl EveryVariant for $ptr<str> {
+                fn every_variant() -> Vec<Self> {
+                    Self::every_variant_iter().collect()
+                }
+
+                fn every_variant_iter() -> impl Iterator<Item = Self> {
+                    String::every_variant_iter().map(Self::from)
+                }
+
+                fn variant_count() -> usize {
+                    String::variant_count()
+                }
+
+                fn variant_index(&self) -> Option<usize> {
+                    position_of(self)
+                }
+            }
+
+            /// The same values as `Vec<T>`
+            impl<T: EveryVariant + Clone> EveryVariant for $ptr<[T]> {
+                fn every_variant() -> Vec<Self> {
+                    Self::every_variant_iter().collect()
+                }
+
+                fn every_variant_iter() -> impl Iterator<Item = Self> {
+                    Vec::<T>::every_variant_iter().map(Self::from)
+                }
+
+                fn variant_count() -> usize {
+                    Vec::<T>::variant_count()
+                }
+
+                fn every_variant_shallow() -> Vec<Self> {
+                    Vec::<T>::every_variant_shallow()
+                        .into_iter()
+                        .map(Self::from)
+                        .collect()
+                }
+
+                fn variant_index(&self) -> Option<usize> {
+                    self.to_vec().variant_index()
+                }
+
+                fn variant_paths(prefix: &str, paths: &mut Vec<String>) {
+                    Vec::<T>::variant_paths(prefix, paths);
+                }
+
+                fn observed_variant_paths(&self, prefix: &str, paths: &mut Vec<String>) {
+                    for value in self.iter() {
+                        value.observed_variant_paths(prefix, paths);
+                    }
+                }
+            }
+        )+
+    };
+}
+
+pointer_impl!(Box, Rc, Arc);
+
+/// Which values of a type a leaked value is one of, and its position among them
+#[derive(PartialEq, Eq, Hash)]
+struct LeakedKey {
+    ty: TypeId,
+    exhaustive: bool,
+    depths: (usize, Vec<(&'static str, usize)>),
+    index: usize,
+}
+
+/// The values handed out by `Cow::Borrowed`, leaked once for the whole process
+static LEAKED: OnceLock<Mutex<HashMap<LeakedKey, &'static (dyn Any + Send + Sync)>>> =
+    OnceLock::new();
+
+/// `value`, at `index` among the variants of `T`, leaked the first time it is asked for and
+/// the same reference every time after. The profile and depths are part of the key, as they
+/// change the variants of a type.
+fn leaked<T: Send + Sync + 'static>(index: usize, value: T) -> &'static T {
+    let key = LeakedKey {
+        ty: TypeId::of::<T>(),
+        exhaustive: profile::is_exhaustive(),
+        depths: depth::state(),
+        index,
+    };
+    let leaked: &'static (dyn Any + Send + Sync) = *LEAKED
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .entry(key)
+        .or_insert_with(|| Box::leak(Box::new(value)));
+    leaked
+        .downcast_ref()
+        .expect("leaked values are keyed by their type")
+}
+
+/// Every variant of `T` borrowed, followed by every variant of `T` owned, as code often
+/// branches on which of the two it gets. The borrowed values have to live for `'static`, so
+/// they are leaked, once for every value, which is why they have to be `Send` and `Sync`.
+impl<T: EveryVariant + Clone + Send + Sync + 'static> EveryVariant for Cow<'static, T> {
+    fn every_variant() -> Vec<Self> {
+        Self::every_variant_iter().collect()
+    }
+
+    fn every_variant_iter() -> impl Iterator<Item = Self> {
+        let borrowed = T::every_variant_iter()
+            .enumerate()
+            .map(|(index, value)| Cow::Borrowed(leaked(index, value)));
+        let owned = T::every_variant_iter().map(Cow::Owned);
+        borrowed.chain(owned)
+    }
+
+    fn variant_count() -> usize {
+        T::variant_count().saturating_mul(2)
+    }
+
+    fn nth_variant(index: usize) -> Option<Self> {
+        let count = T::variant_count();
+        if index < count {
+            T::nth_variant(index).map(|value| Cow::Borrowed(leaked(index, value)))
+        } else {
+            T::nth_variant(index - count).map(Cow::Owned)
+        }
+    }
+
+    fn variant_index(&self) -> Option<usize> {
+        match self {
+            Cow::Borrowed(value) => value.variant_index(),
+            Cow::Owned(value) => value.variant_index()?.checked_add(T::variant_count()),
+        }
+    }
+
+    fn variant_paths(prefix: &str, paths: &mut Vec<String>) {
+        if T::variant_count() != 0 {
+            paths.push(format!("{}Cow::Borrowed", prefix));
+            T::variant_paths(&format!("{}Cow::Borrowed.0.", prefix), paths);
+            paths.push(format!("{}Cow::Owned", prefix));
+            T::variant_paths(&format!("{}Cow::Owned.0.", prefix), paths);
+        }
+    }
+
+    fn observed_variant_paths(&self, prefix: &str, paths: &mut Vec<String>) {
+        let form = match self {
+            Cow::Borrowed(_) => "Borrowed",
+            Cow::Owned(_) => "Owned",
+        };
+        paths.push(format!("{}Cow::{}", prefix, form));
+        self.as_ref()
+            .observed_variant_paths(&format!("{}Cow::{}.0.", prefix, form), paths);
+    }
+}
+
+/// The `&'static str` variants borrowed, followed by the `String` variants owned
+impl EveryVariant for Cow<'static, str> {
+    fn every_variant() -> Vec<Self> {
+        Self::every_variant_iter().collect()
+    }
+
+    fn every_variant_iter() -> impl Iterator<Item = Self> {
+        let borrowed = <&'static str>::every_variant_iter().map(Cow::Borrowed);
+        let owned = String::every_variant_iter().map(Cow::Owned);
+        borrowed.chain(owned)
+    }
+
+    fn variant_count() -> usize {
+        <&'static str>::variant_count().saturating_add(String::variant_count())
+    }
+
+    fn variant_index(&self) -> Option<usize> {
+        match self {
+            Cow::Borrowed(value) => value.variant_index(),
+            Cow::Owned(value) => value
+                .variant_index()?
+                .checked_add(<&'static str>::variant_count()),
+        }
+    }
+
+    fn variant_paths(prefix: &str, paths: &mut Vec<String>) {
+        paths.push(format!("{}Cow::Borrowed", prefix));
+        paths.push(format!("{}Cow::Owned", prefix));
+    }
+
+    fn observed_variant_paths(&self, prefix: &str, paths: &mut Vec<String>) {
+        match self {
+            Cow::Borrowed(_) => paths.push(format!("{}Cow::Borrowed", prefix)),
+            Cow::Owned(_) => paths.push(format!("{}Cow::Owned", prefix)),
+        }
+    }
+}
+
+// Tuples are the cartesian product of their elements, the first element changing slowest.
+// Every impl is built from the one for its tail, ending with the one for `()`
+macro_rules! tuple_impl {
+    ($head: ident $(, $tail: ident)*) => {
+        impl<$head: EveryVariant + Clone, $($tail: EveryVariant + Clone),*> EveryVariant
+            for ($head, $($tail,)*)
+        {
+            fn every_variant() -> Vec<Self> {
+                Self::every_variant_iter().collect()
+            }
+
+            #[allow(non_snake_case)]
+            fn every_variant_iter() -> impl Iterator<Item = Self> {
+                product($head::every_variant_iter(), |head| {
+                    <($($tail,)*)>::every_variant_iter()
+                        .map(move |($($tail,)*)| (head.clone(), $($tail,)*))
+                })
+            }
+
+            fn variant_count() -> usize {
+                $head::variant_count().saturating_mul(<($($tail,)*)>::variant_count())
+            }
+
+            #[allow(non_snake_case)]
+            fn every_variant_shallow() -> Vec<Self> {
+                let ($head, $($tail,)*) = (
+                    $head::every_variant_shallow(),
+                    $($tail::every_variant_shallow(),)*
+                );
+                let sizes = [$head.len(), $($tail.len()),*];
+
+                covering::one_at_a_time(&sizes)
+                    .into_iter()
+                    .map(|row| {
+                        let mut row = row.into_iter();
+                        let mut next = || row.next().unwrap_or_default();
+                        ($head[next()].clone(), $($tail[next()].clone(),)*)
+                    })
+                    .collect()
+            }
+
+            #[allow(non_snake_case)]
+            fn nth_variant(index: usize) -> Option<Self> {
+                let tails = <($($tail,)*)>::variant_count();
+                if tails == 0 {
+                    return None;
+                }
+                let head = $head::nth_variant(index / tails)?;
+                let ($($tail,)*) = <($($tail,)*)>::nth_variant(index % tails)?;
+                Some((head, $($tail,)*))
+            }
+
+            #[allow(non_snake_case)]
+            fn variant_index(&self) -> Option<usize> {
+                // the first element is the most significant digit
+                let ($head, $($tail,)*) = self;
+                let index = $head.variant_index()?;
+                $(
+                    let index = index
+                        .checked_mul($tail::variant_count())?
+                        .checked_add($tail.variant_index()?)?;
+                )*
+                Some(index)
+            }
+
+            fn variant_paths(prefix: &str, paths: &mut Vec<String>) {
+                let mut position = 0usize..;
+                let mut element = || format!("{}{}.", prefix, position.next().unwrap_or_default());
+                $head::variant_paths(&element(), paths);
+                $( $tail::variant_paths(&element(), paths); )*
+            }
+
+            #[allow(non_snake_case)]
+            fn observed_variant_paths(&self, prefix: &str, paths: &mut Vec<String>) {
+                let ($head, $($tail,)*) = self;
+                let mut position = 0usize..;
+                let mut element = || format!("{}{}.", prefix, position.next().unwrap_or_default());
+                $head.observed_variant_paths(&element(), paths);
+                $( $tail.observed_variant_paths(&element(), paths); )*
+            }
+
+            fn random_variant(rng: &mut Rng) -> Option<Self> {
+                Some(($head::random_variant(rng)?, $($tail::random_variant(rng)?,)*))
+            }
+
+            #[allow(non_snake_case)]
+            fn covering_variants(strength: usize) -> (Vec<Self>, Coverage) {
+                let ($head, $($tail,)*) = (
+                    $head::every_t_wise(strength),
+                    $($tail::every_t_wise(strength),)*
+                );
+                let sizes = [$head.len(), $($tail.len()),*];
+                let rows = covering::covering_array(&sizes, strength);
+
+                let mut coverage = covering::coverage(&sizes, strength, &rows);
+                coverage.full_product = Self::variant_count();
+
+                let vec = rows
+                    .into_iter()
+                    .map(|row| {
+                        let mut row = row.into_iter();
+                        let mut next = || row.next().unwrap_or_default();
+                        ($head[next()].clone(), $($tail[next()].clone(),)*)
+                    })
+                    .collect();
+                (vec, coverage)
+            }
+        }
+
+        tuple_impl!($($tail),*);
+    };
+    () => {};
+}
+
+tuple_impl!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+/// Arrays are not the full product of their elements, that grows too quickly with the length.
+/// Instead there is one array filled with each variant of `T`, followed by arrays mixing the
+/// variants: the `k`th of those holds variant `(k + i) % count` at index `i`, so every variant
+/// shows up at every index. Use [`FullArray`] to get every combination instead.
+impl<T: EveryVariant + Clone, const N: usize> EveryVariant for [T; N] {
+    fn every_variant() -> Vec<Self> {
+        Self::every_variant_iter().collect()
+    }
+
+    fn every_variant_iter() -> impl Iterator<Item = Self> {
+        iter::once_with(T::every_variant).flat_map(array_variants)
+    }
+
+    fn variant_count() -> usize {
+        let count = T::variant_count();
+        let same = if N == 0 { count.min(1) } else { count };
+        let mixed = if N > 1 && count > 1 { count } else { 0 };
+        same + mixed
+    }
+
+    fn every_variant_shallow() -> Vec<Self> {
+        // the arrays filled with a single value already have every variant at every index
+        let values = T::every_variant_shallow();
+        let same = if N == 0 {
+            values.len().min(1)
+        } else {
+            values.len()
+        };
+        values[..same]
+            .iter()
+            .map(|value| std::array::from_fn(|_| value.clone()))
+            .collect()
+    }
+
+    fn nth_variant(index: usize) -> Option<Self> {
+        let count = T::variant_count();
+        let same = if N == 0 { count.min(1) } else { count };
+        if index < same {
+            let value = T::nth_variant(index)?;
+            return Some(std::array::from_fn(|_| value.clone()));
+        }
+
+        let mixed = index - same;
+        if N < 2 || count < 2 || mixed >= count {
+            return None;
+        }
+        let values: Vec<T> = (0..N)
+            .map(|i| T::nth_variant((mixed + i) % count))
+            .collect::<Option<_>>()?;
+        values.try_into().ok()
+    }
+
+    fn variant_index(&self) -> Option<usize> {
+        let count = T::variant_count();
+        let indexes: Vec<usize> = self
+            .iter()
+            .map(EveryVariant::variant_index)
+            .collect::<Option<_>>()?;
+
+        let first = match indexes.first() {
+            Some(&first) => first,
+            // the empty array is only generated when `T` has a variant to fill it with
+            None if Self::variant_count() > 0 => return Some(0),
+            None => return None,
+        };
+        if indexes.iter().all(|&index| index == first) {
+            return Some(first);
+        }
+
+        // the variants of `T` can repeat, so the mixed arrays are compared through the index
+        // each variant of `T` gets itself
+        let own: Vec<usize> = (0..count)
+            .map(|variant| T::nth_variant(variant)?.variant_index())
+            .collect::<Option<_>>()?;
+        (0..count)
+            .find(|&k| {
+                indexes
+                    .iter()
+                    .enumerate()
+                    .all(|(i, &index)| index == own[(k + i) % count])
+            })
+            .map(|k| count + k)
+    }
+
+    fn variant_paths(prefix: &str, paths: &mut Vec<String>) {
+        T::variant_paths(prefix, paths);
+    }
+
+    fn observed_variant_paths(&self, prefix: &str, paths: &mut Vec<String>) {
+        for value in self {
+            value.observed_variant_paths(prefix, paths);
+        }
+    }
+}
+
+/// The arrays `every_variant` gives for `[T; N]`, from `variants` in place of the variants of `T`
+fn array_variants<T: Clone, const N: usize>(variants: Vec<T>) -> impl Iterator<Item = [T; N]> {
+    let count = variants.len();
+    // arrays without elements all look the same
+    let same = if N == 0 { count.min(1) } else { count };
+    let mixed = if N > 1 && count > 1 { count } else { 0 };
+
+    (0..same + mixed).map(move |k| {
+        if k < same {
+            std::array::from_fn(|_| variants[k].clone())
+        } else {
+            std::array::from_fn(|i| variants[(k - same + i) % count].clone())
+        }
+    })
+}
+
+/// Array with every combination of variants of `T` at every index, that is `count.pow(N)`
+/// arrays, the first index changing slowest. Only meant for small `N`, the arrays in between
+/// are not kept in memory but all of them are walked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FullArray<T, const N: usize>(pub [T; N]);
+
+impl<T: EveryVariant + Clone, const N: usize> EveryVariant for FullArray<T, N> {
+    fn every_variant() -> Vec<Self> {
+        Self::every_variant_iter().collect()
+    }
+
+    fn every_variant_iter() -> impl Iterator<Item = Self> {
+        iter::once_with(T::every_variant).flat_map(full_arrays)
+    }
+
+    fn variant_count() -> usize {
+        let exp = u32::try_from(N).unwrap_or(u32::MAX);
+        T::variant_count().saturating_pow(exp)
+    }
+
+    fn every_variant_shallow() -> Vec<Self> {
+        <[T; N]>::every_variant_shallow()
+            .into_iter()
+            .map(FullArray)
+            .collect()
+    }
+
+    fn nth_variant(mut index: usize) -> Option<Self> {
+        let count = T::variant_count();
+        if index >= Self::variant_count() {
+            return None;
+        }
+
+        // the last index is the least significant digit
+        let mut digits = [0; N];
+        for digit in digits.iter_mut().rev() {
+            *digit = index % count;
+            index /= count;
+        }
+
+        let values: Vec<T> = digits
+            .iter()
+            .map(|&digit| T::nth_variant(digit))
+            .collect::<Option<_>>()?;
+        values.try_into().ok().map(FullArray)
+    }
+
+    fn variant_index(&self) -> Option<usize> {
+        let count = T::variant_count();
+        self.0.iter().try_fold(0usize, |index, value| {
+            index
+                .checked_mul(count)?
+                .checked_add(value.variant_index()?)
+        })
+    }
+
+    fn variant_paths(prefix: &str, paths: &mut Vec<String>) {
+        T::variant_paths(prefix, paths);
+    }
+
+    fn observed_variant_paths(&self, prefix: &str, paths: &mut Vec<String>) {
+        for value in &self.0 {
+            value.observed_variant_paths(prefix, paths);
+        }
+    }
+}
+
+/// Every combination of `variants` at every index, the first index changing slowest
+fn full_arrays<T: Clone, const N: usize>(
+    variants: Vec<T>,
+) -> impl Iterator<Item = FullArray<T, N>> {
+    let count = variants.len();
+    let first = if count == 0 && N > 0 {
+        None
+    } else {
+        Some([0; N])
+    };
+
+    // counts through the indexes into `variants` like an odometer
+    let indexes = iter::successors(first, move |indexes: &[usize; N]| {
+        let mut indexes = *indexes;
+        for idx in indexes.iter_mut().rev() {
+            *idx += 1;
+            if *idx < count {
+                return Some(indexes);
+            }
+            *idx = 0;
+        }
+        None
+    });
+
+    indexes.map(move |indexes| FullArray(indexes.map(|idx| variants[idx].clone())))
+}
+
+#[cfg(feature = "ev_heapless")]
+use heapless::{String as HString, Vec as HVec};
+
+#[cfg(feature = "ev_heapless")]
+impl<T, const N: usize> EveryVariant for HVec<T, N>
+where
+    T: EveryVariant + Clone + Sized,
+{
+    fn every_variant() -> Vec<Self> {
+        Self::every_variant_iter().collect()
+    }
+
+    fn every_variant_iter() -> impl Iterator<Item = Self> {
+        iter::once_with(|| {
+            let mut vec = HVec::new();
+
+            for v in T::every_variant_iter() {
+                if vec.push(v).is_err() {
+                    break;
+                }
+            }
+
+            vec
+        })
+    }
+
+    fn variant_count() -> usize {
+        1
+    }
+
+    fn variant_index(&self) -> Option<usize> {
+        // compared by the indexes of the elements, which can repeat like in `Vec<T>`
+        let indexes = |vec: &Self| -> Option<Vec<usize>> {
+            vec.iter().map(EveryVariant::variant_index).collect()
+        };
+        let own = indexes(self)?;
+        Self::every_variant_iter().position(|vec| indexes(&vec).as_ref() == Some(&own))
+    }
+
+    fn variant_paths(prefix: &str, paths: &mut Vec<String>) {
+        T::variant_paths(prefix, paths);
+    }
+
+    fn observed_variant_paths(&self, prefix: &str, paths: &mut Vec<String>) {
+        for value in self {
+            value.observed_variant_paths(prefix, paths);
+        }
+    }
+}
+
+#[cfg(feature = "ev_heapless")]
+impl<const N: usize> EveryVariant for HString<N> {
+    fn every_variant() -> Vec<Self> {
+        let mut s = HString::new();
+        s.push_str("hello").ok();
+        if profile::is_exhaustive() {
+            edge_cases::with_edge_cases(vec![s])
+        } else {
+            vec![s]
+        }
+    }
+
+    fn variant_index(&self) -> Option<usize> {
+        position_of(self)
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::EveryVariant;
+    #[cfg(feature = "ev_heapless")]
+    use heapless::{String as HString, Vec as HVec};
+    use std::fmt;
+
+    /// Type of the message
+    #[derive(EveryVariant, Debug, Clone)]
+    enum MessageType {
+        Codified,
+        Markdown,
+        Html,
+    }
+
+    /// This type can come in  4 different variants due the option
+    #[derive(EveryVariant, Debug, Clone)]
+    struct FormattedMessage {
+        /// Enum dictating how to render the string, None means its hidden
+        rendermethod: Option<MessageType>,
+        /// The optional content of the message
+        text: String,
+    }
+
+    #[test]
+    fn small_example() {
+        let all_diferent_messages = FormattedMessage::every_variant();
+        println!("{:#?}", all_diferent_messages);
+
+        let opt_msg_len = Option::<MessageType>::every_variant().len();
+        let text_len = String::every_variant().len();
+
+        assert_eq!(opt_msg_len * text_len, all_diferent_messages.len());
+    }
+
+    #[cfg(feature = "ev_heapless")]
+    #[test]
+    fn heapless() {
+        let _s = HString::<16>::every_variant();
+        let _v = HVec::<u8, 16>::every_variant();
+
+        let strings = HString::<16>::every_variant_exhaustive();
+        assert_eq!("hello", strings[0].as_str());
+        assert!(strings.iter().any(|s| s.is_empty()));
+        assert_eq!(
+            u8::every_variant_exhaustive().len(),
+            HVec::<u8, 16>::every_variant_exhaustive()[0].len()
+        );
+    }
+
+    #[derive(EveryVariant, Debug, Clone)]
+    pub struct Message {
+        pub message: String,
+        pub number: u32,
+        pub opt: Option<u64>,
+        pub nest: Top,
+        pub second: SecondTop,
+    }
+
+    #[derive(EveryVariant, Debug, Clone, PartialEq)]
+    pub enum SecondTop {
+        One,
+        Two(Nested),
+        Three,
+    }
+
+    #[derive(EveryVariant, Debug, Clone, PartialEq, Eq, Hash)]
+    pub enum Top {
+        One,
+        Nested(Nested),
+    }
+
+    #[derive(EveryVariant, Debug, Clone, PartialEq, Eq, Hash)]
+    pub enum Nested {
+        First,
+        Second,
+        Third,
+    }
+
+    #[derive(EveryVariant, Debug, Clone)]
+    pub struct TestUnnamed3(pub u16);
+
+    #[test]
+    fn messages_number() {
+        let msgs = Message::every_variant().len();
+        let messages_len = String::every_variant().len();
+        let number_len = u32::every_variant().len();
+        let opt_len = Option::<u64>::every_variant().len();
+        let nest_len = Top::every_variant().len();
+        let second_len = SecondTop::every_variant().len();
+
+        assert_eq!(
+            (messages_len * number_len * opt_len * nest_len * second_len),
+            msgs
+        );
+    }
+
+    #[test]
+    fn iter_matches_vec() {
+        let from_vec: Vec<String> = Message::every_variant()
+            .iter()
+            .map(|m| format!("{:?}", m))
+            .collect();
+        let from_iter: Vec<String> = Message::every_variant_iter()
+            .map(|m| format!("{:?}", m))
+            .collect();
+        assert_eq!(from_vec, from_iter);
+
+        let vec_len = Vec::<Option<Nested>>::every_variant().len();
+        assert_eq!(vec_len, Vec::<Option<Nested>>::every_variant_iter().count());
+    }
+
+    #[derive(EveryVariant, Debug, Clone)]
+    pub struct Huge(Message, Message, Message, Message, Message, Message);
+
+    #[test]
+    fn iter_is_lazy() {
+        // 40^6 combinations, far too many to collect into a vector
+        let first: Vec<Huge> = Huge::every_variant_iter().take(3).collect();
+        assert_eq!(3, first.len());
+    }
+
+    #[test]
+    fn opts_number() {
+        let msgs = Option::<u64>::every_variant().len();
+        assert_eq!(u64::every_variant().len() + 1, msgs);
+    }
+
+    #[derive(EveryVariant, Debug, Clone)]
+    pub enum TestUnnamed1 {
+        UnnamedSingle(u16),
+        UnnamedMultiple1(u16, u32),
+        UnnamedMultiple2(u16, u32, u64, i32),
+    }
+
+    #[test]
+    fn unnamed1() {
+        let msgs = TestUnnamed1::every_variant().len();
+        let u16_len = u16::every_variant().len();
+        let u32_len = u32::every_variant().len();
+        let u64_len = u64::every_variant().len();
+        let i32_len = i32::every_variant().len();
+        assert_eq!(
+            u16_len + u16_len * u32_len + u16_len * u32_len * u64_len * i32_len,
+            msgs
+        );
+    }
+
+    #[derive(EveryVariant, Debug, Clone)]
+    pub struct TestUnnamed2(u16, u32, u64);
+
+    #[test]
+    fn unnamed2() {
+        let msgs = TestUnnamed2::every_variant().len();
+        let u16_len = u16::every_variant().len();
+        let u32_len = u32::every_variant().len();
+        let u64_len = u64::every_variant().len();
+        assert_eq!(u16_len * u32_len * u64_len, msgs);
+    }
+
+    #[derive(EveryVariant, Debug, Clone)]
+    pub struct Gen1<A: EveryVariant + Clone>(A);
+
+    #[derive(EveryVariant, Debug, Clone)]
+    pub struct Gen2<A: EveryVariant + Clone, B: EveryVariant + Clone>(A, B);
+
+    #[derive(EveryVariant, Debug, Clone)]
+    pub struct Generic1(Gen1<u8>, Gen2<u16, u32>);
+    #[test]
+    fn generic1() {
+        let msgs = Generic1::every_variant().len();
+
+        let gen1_len = Gen1::<u8>::every_variant().len();
+        let gen2_len = Gen2::<u16, u32>::every_variant().len();
+
+        assert_eq!(gen1_len * gen2_len, msgs);
+    }
+
+    #[derive(EveryVariant, Debug, Clone)]
+    pub enum Generic2 {
+        G1(Gen1<i8>),
+        G2(Gen2<i16, i32>),
+    }
+
+    #[test]
+    fn generic2() {
+        let msgs = Generic2::every_variant().len();
+        let gen1_len = Gen1::<i8>::every_variant().len();
+        let gen2_len = Gen2::<i16, i32>::every_variant().len();
+        assert_eq!(gen1_len + gen2_len, msgs);
+    }
+
+    #[derive(EveryVariant, Debug, Clone)]
+    pub enum TestNamed1 {
+        NamedSingle { first: u16 },
+        NamedMultiple1 { first: u16, second: u32 },
+        NamedMultiple2 { first: u16, second: u32, third: u64 },
+    }
+
+    #[test]
+    fn named_enum() {
+        let msgs = TestNamed1::every_variant().len();
+        let u16_len = u16::every_variant().len();
+        let u32_len = u32::every_variant().len();
+        let u64_len = u64::every_variant().len();
+        assert_eq!(
+            u16_len + u16_len * u32_len + u16_len * u32_len * u64_len,
+            msgs
+        );
+    }
+
+    #[allow(unused)]
+    #[derive(EveryVariant)]
+    pub struct GenericDerive<T> {
+        value: T,
+    }
+
+    #[allow(unused)]
     #[derive(EveryVariant)]
     pub enum GenericEnum<T> {
         One(T),
         Two(u32),
     }
 
-    #[allow(unused)]
-    #[derive(EveryVariant)]
-    pub struct MultiGeneric<A, B>(A, B);
+    #[allow(unused)]
+    #[derive(EveryVariant)]
+    pub struct MultiGeneric<A, B>(A, B);
+
+    #[test]
+    fn generic_everyvariant() {
+        let msgs = GenericDerive::<u32>::every_variant().len();
+        assert_eq!(u32::every_variant().len(), msgs);
+
+        let msgs = GenericEnum::<u32>::every_variant().len();
+        assert_eq!(2 * u32::every_variant().len(), msgs);
+
+        let msgs = MultiGeneric::<u32, u32>::every_variant().len();
+        assert_eq!(
+            u32::every_variant().len() * u32::every_variant().len(),
+            msgs
+        );
+    }
+
+    #[derive(EveryVariant, Debug, Clone)]
+    pub struct Request {
+        #[every_variant(values = ["GET", "POST"], partial_eq)]
+        method: String,
+        #[every_variant(partial_eq, values = [80, 443, 8080])]
+        port: u16,
+        secure: bool,
+    }
+
+    #[derive(EveryVariant, Debug, Clone, PartialEq)]
+    pub struct Port(#[every_variant(values = [1, u16::MAX], partial_eq)] u16);
+
+    #[derive(EveryVariant, Debug, Clone)]
+    pub enum Route {
+        Static(#[every_variant(values = ["/", "/index.html"], partial_eq)] &'static str),
+        Proxy {
+            #[every_variant(values = [Port(3000)], partial_eq)]
+            port: Port,
+            secure: bool,
+        },
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct Slot(u8);
+
+    #[derive(EveryVariant, Debug, Clone)]
+    pub struct Pool {
+        #[every_variant(values = [Slot(1), Slot(2)])]
+        slot: Slot,
+        busy: bool,
+    }
+
+    #[test]
+    fn values_attribute() {
+        let requests = Request::every_variant();
+        assert_eq!(2 * 3 * 2, requests.len());
+        assert_eq!("GET", requests[0].method);
+        assert_eq!(443, requests[2].port);
+        assert_eq!("POST", requests[6].method);
+
+        let ports: Vec<u16> = Port::every_variant().into_iter().map(|p| p.0).collect();
+        assert_eq!(vec![1, u16::MAX], ports);
+
+        let routes = Route::every_variant();
+        assert_eq!(2 + 2, routes.len());
+        assert!(matches!(routes[1], Route::Static("/index.html")));
+        assert!(matches!(
+            routes[3],
+            Route::Proxy {
+                port: Port(3000),
+                secure: false
+            }
+        ));
+
+        // without `partial_eq` the listed values need no `PartialEq`, and give no index
+        let pools = Pool::every_variant();
+        assert_eq!(2 * 2, pools.len());
+        assert_eq!(2, pools[2].slot.0);
+        assert!(pools.iter().all(|pool| pool.variant_index().is_none()));
+        assert_eq!(Some(2), requests[2].variant_index());
+    }
+
+    #[derive(Debug)]
+    pub struct Handle(u32);
+
+    #[derive(EveryVariant, Debug)]
+    pub struct Worker {
+        #[every_variant(skip)]
+        jobs: Vec<String>,
+        #[every_variant(default = Handle(7))]
+        handle: Handle,
+        busy: bool,
+    }
+
+    #[derive(EveryVariant, Debug, Clone)]
+    pub enum Command {
+        Start,
+        #[every_variant(skip)]
+        Deprecated(Handle),
+        Stop(#[every_variant(skip)] Option<u8>, bool),
+    }
+
+    impl Clone for Handle {
+        fn clone(&self) -> Self {
+            unreachable!("skipped fields should never be cloned")
+        }
+    }
+
+    #[test]
+    fn skip_attribute() {
+        let workers = Worker::every_variant();
+        assert_eq!(2, workers.len());
+        assert!(workers.iter().all(|w| w.jobs.is_empty() && w.handle.0 == 7));
+
+        let commands = Command::every_variant();
+        assert_eq!(1 + 2, commands.len());
+        assert!(commands
+            .iter()
+            .all(|c| !matches!(c, Command::Deprecated(_) | Command::Stop(Some(_), _))));
+    }
+
+    #[derive(EveryVariant, Debug, Clone)]
+    pub struct NonPathFields {
+        name: &'static str,
+        unit: (),
+        nested: Option<Nested>,
+    }
+
+    macro_rules! wrapper {
+        ($name: ident, $ty: ty) => {
+            #[derive(EveryVariant, Debug, Clone)]
+            pub struct $name(Option<$ty>, &'static str);
+        };
+    }
+
+    wrapper!(FromMacro, Nested);
+
+    #[test]
+    fn non_path_fields() {
+        let msgs = NonPathFields::every_variant().len();
+        assert_eq!(Option::<Nested>::every_variant().len(), msgs);
+
+        let msgs = FromMacro::every_variant().len();
+        assert_eq!(Option::<Nested>::every_variant().len(), msgs);
+    }
+
+    #[test]
+    fn tuples() {
+        let pairs = <(u8, bool)>::every_variant();
+        assert_eq!(vec![(8, true), (8, false)], pairs);
+
+        let msgs = <(Nested, Option<Top>, bool)>::every_variant().len();
+        let nested_len = Nested::every_variant().len();
+        let top_len = Option::<Top>::every_variant().len();
+        assert_eq!(nested_len * top_len * 2, msgs);
+
+        let msgs = <(
+            bool,
+            bool,
+            bool,
+            bool,
+            bool,
+            bool,
+            bool,
+            bool,
+            bool,
+            bool,
+            bool,
+            bool,
+        )>::every_variant_iter()
+        .count();
+        assert_eq!(1 << 12, msgs);
+
+        assert_eq!(vec![(8u8,)], <(u8,)>::every_variant());
+    }
+
+    #[derive(EveryVariant, Debug, Clone)]
+    pub struct TupleFields {
+        pair: Option<(Nested, bool)>,
+        unit_tuple: (u16,),
+    }
+
+    #[test]
+    fn tuple_fields() {
+        let msgs = TupleFields::every_variant().len();
+        assert_eq!(1 + Nested::every_variant().len() * 2, msgs);
+    }
+
+    #[test]
+    fn arrays() {
+        let arrays = <[bool; 3]>::every_variant();
+        assert_eq!(
+            vec![
+                [true, true, true],
+                [false, false, false],
+                [true, false, true],
+                [false, true, false]
+            ],
+            arrays
+        );
+
+        assert_eq!(3 + 3, <[Nested; 4]>::every_variant().len());
+        assert_eq!(vec![[8u8; 4]], <[u8; 4]>::every_variant());
+        assert_eq!(1, <[Nested; 0]>::every_variant().len());
+        assert_eq!(3, <[Nested; 1]>::every_variant().len());
+
+        // without a variant of the element there is no empty array either
+        let empty: [Uninhabited; 0] = [];
+        assert_eq!(0, <[Uninhabited; 0]>::variant_count());
+        assert_eq!(None, empty.variant_index());
+        assert_eq!(Some(0), <[Nested; 0]>::default().variant_index());
+    }
+
+    #[test]
+    fn full_arrays() {
+        let arrays = crate::FullArray::<bool, 2>::every_variant();
+        assert_eq!(
+            vec![[true, true], [true, false], [false, true], [false, false]],
+            arrays.into_iter().map(|a| a.0).collect::<Vec<_>>()
+        );
+
+        assert_eq!(
+            27,
+            crate::FullArray::<Nested, 3>::every_variant_iter().count()
+        );
+        assert_eq!(1, crate::FullArray::<Nested, 0>::every_variant().len());
+    }
+
+    #[derive(EveryVariant, Debug, Clone)]
+    pub struct Packet {
+        address: [u8; 4],
+        flags: [Option<Nested>; 3],
+    }
+
+    #[test]
+    fn array_fields() {
+        let msgs = Packet::every_variant().len();
+        assert_eq!(<[Option<Nested>; 3]>::every_variant().len(), msgs);
+    }
+
+    /// Runs the generic `check` for each of the types
+    macro_rules! check_types {
+        ($check: ident: $($ty: ty),+ $(,)?) => {
+            $( $check::<$ty>(); )+
+        };
+    }
+
+    /// `variant_count` agrees with the length of `every_variant`
+    fn check_count<T: EveryVariant>() {
+        let name = std::any::type_name::<T>();
+        assert_eq!(T::every_variant().len(), T::variant_count(), "{}", name);
+    }
+
+    /// `nth_variant` picks out the values of `every_variant`, in the same order
+    fn check_nth<T: EveryVariant + fmt::Debug>() {
+        let name = std::any::type_name::<T>();
+        let every: Vec<String> = T::every_variant()
+            .iter()
+            .map(|v| format!("{:?}", v))
+            .collect();
+        let nth: Vec<String> = (0..every.len())
+            .map(|index| format!("{:?}", T::nth_variant(index).unwrap()))
+            .collect();
+        assert_eq!(every, nth, "{}", name);
+        assert!(T::nth_variant(every.len()).is_none(), "{}", name);
+    }
+
+    /// Every value of `every_variant` has a `variant_index`, and `nth_variant` gives it back.
+    /// Compared by `Debug`, as not every type implements `PartialEq`.
+    fn check_index<T: EveryVariant + fmt::Debug>() {
+        let name = std::any::type_name::<T>();
+        for value in T::every_variant_iter() {
+            let index = value.variant_index();
+            assert!(index.is_some(), "{}: {:?}", name, value);
+            let nth = index.and_then(T::nth_variant);
+            assert_eq!(
+                format!("{:?}", Some(&value)),
+                format!("{:?}", nth.as_ref()),
+                "{}",
+                name
+            );
+        }
+    }
+
+    /// `check_index` for types printed in no fixed order, compared with `PartialEq` instead
+    fn check_index_eq<T: EveryVariant + PartialEq + fmt::Debug>() {
+        let name = std::any::type_name::<T>();
+        for value in T::every_variant_iter() {
+            let nth = value.variant_index().and_then(T::nth_variant);
+            assert_eq!(Some(&value), nth.as_ref(), "{}", name);
+        }
+    }
+
+    /// `every_variant_shallow` has every enum variant, and only values of `every_variant`
+    fn check_shallow<T: EveryVariant + fmt::Debug>() {
+        let mut coverage = crate::VariantCoverage::<T>::new();
+        for value in &T::every_variant_shallow() {
+            assert!(value.variant_index().is_some(), "{:?}", value);
+            coverage.record(value);
+        }
+        coverage.assert_complete();
+    }
+
+    #[test]
+    fn variant_counts() {
+        check_types!(
+            check_count:
+            (),
+            String,
+            f32,
+            std::num::NonZeroI8,
+            Option<Nested>,
+            Result<Top, u8>,
+            Vec<SecondTop>,
+            Vec<Vec<Nested>>,
+            (u8, bool, Nested),
+            [Nested; 3],
+            [Nested; 0],
+            [bool; 1],
+            crate::FullArray<Nested, 3>,
+            Message,
+            TestUnnamed1,
+            TestNamed1,
+            Generic2,
+            Request,
+            Route,
+            Worker,
+            Command,
+            Packet
+        );
+    }
 
     #[test]
-    fn generic_everyvariant() {
-        let msgs = GenericDerive::<u32>::every_variant().len();
-        assert_eq!(u32::every_variant().len(), msgs);
+    fn variant_count_saturates() {
+        assert_eq!(usize::MAX, crate::FullArray::<bool, 128>::variant_count());
+        assert_eq!(
+            usize::MAX,
+            <(crate::FullArray<bool, 128>, Nested)>::variant_count()
+        );
+    }
 
-        let msgs = GenericEnum::<u32>::every_variant().len();
-        assert_eq!(2 * u32::every_variant().len(), msgs);
+    #[derive(EveryVariant, Debug, Clone, PartialEq)]
+    pub struct Settings {
+        first: Nested,
+        second: Option<Nested>,
+        third: Nested,
+        fourth: Top,
+        flag: bool,
+    }
 
-        let msgs = MultiGeneric::<u32, u32>::every_variant().len();
+    #[derive(EveryVariant, Debug, Clone, PartialEq)]
+    pub enum Config {
+        Empty,
+        Full(Settings, Nested, Nested),
+    }
+
+    #[test]
+    fn every_pair() {
+        let pairs = Settings::every_pair();
+        assert!(pairs.len() < Settings::variant_count());
+        assert!(pairs.len() <= 20, "{} settings", pairs.len());
+
+        let everything = Settings::every_variant();
+        for a in &everything {
+            for b in &everything {
+                assert!(pairs
+                    .iter()
+                    .any(|p| p.first == a.first && p.second == b.second));
+                assert!(pairs
+                    .iter()
+                    .any(|p| p.third == a.third && p.fourth == b.fourth));
+                assert!(pairs
+                    .iter()
+                    .any(|p| p.second == a.second && p.flag == b.flag));
+            }
+        }
+
+        // the nested settings only contribute their own pairs
+        let configs = Config::every_pair();
+        assert!(matches!(configs[0], Config::Empty));
+        let settings: Vec<&Settings> = configs[1..]
+            .iter()
+            .map(|c| match c {
+                Config::Full(settings, _, _) => settings,
+                Config::Empty => panic!("only one empty config"),
+            })
+            .collect();
+        assert!(pairs.iter().all(|p| settings.contains(&p)));
+        assert!(settings.iter().all(|s| pairs.contains(s)));
+    }
+
+    #[test]
+    fn tuple_pairs() {
+        let pairs = <(bool, bool, bool, bool)>::every_pair();
+        assert!(pairs.len() < 16);
+        for (a, b) in [(true, true), (true, false), (false, true), (false, false)] {
+            assert!(pairs.iter().any(|p| p.0 == a && p.3 == b));
+            assert!(pairs.iter().any(|p| p.1 == a && p.2 == b));
+        }
+        assert_eq!(vec![(8u8,)], <(u8,)>::every_pair());
+    }
+
+    #[derive(EveryVariant, Debug, Clone, PartialEq)]
+    pub struct Safety {
+        first: Nested,
+        second: Nested,
+        third: Nested,
+        fourth: Nested,
+        fifth: Nested,
+    }
+
+    #[test]
+    fn every_t_wise() {
+        let (triples, coverage) = Safety::covering_variants(3);
+        assert_eq!(coverage.covered, coverage.combinations);
+        assert_eq!(10 * 27, coverage.combinations);
+        assert_eq!(triples.len(), coverage.rows);
+        assert_eq!(Safety::variant_count(), coverage.full_product);
+        assert!(triples.len() < Safety::variant_count() / 2);
+
+        let values = Nested::every_variant();
+        for a in &values {
+            for b in &values {
+                for c in &values {
+                    assert!(triples
+                        .iter()
+                        .any(|t| &t.first == a && &t.third == b && &t.fifth == c));
+                    assert!(triples
+                        .iter()
+                        .any(|t| &t.second == a && &t.third == b && &t.fourth == c));
+                }
+            }
+        }
+
+        assert_eq!(Safety::every_pair(), Safety::every_t_wise(2));
+        assert_eq!(Safety::every_variant(), Safety::every_t_wise(5));
+    }
+
+    #[test]
+    fn t_wise_coverage_of_enums() {
+        let (configs, coverage) = Config::covering_variants(2);
+        assert_eq!(configs.len(), coverage.rows);
+        assert_eq!(Config::variant_count(), coverage.full_product);
+        assert_eq!(1.0, coverage.ratio());
+
+        let (_, coverage) = Option::<Config>::covering_variants(2);
+        assert_eq!(Option::<Config>::variant_count(), coverage.full_product);
+        assert_eq!(configs.len() + 1, coverage.rows);
+    }
+
+    #[test]
+    fn sample_variants() {
+        let first = Message::sample_variants(1234, 50);
+        let second = Message::sample_variants(1234, 50);
+        assert_eq!(50, first.len());
+        assert_eq!(format!("{:?}", first), format!("{:?}", second));
+
+        let other = Message::sample_variants(4321, 50);
+        assert_ne!(format!("{:?}", first), format!("{:?}", other));
+
+        // every variant of a small tree shows up eventually
+        let samples = SecondTop::sample_variants(1, 100);
+        for variant in SecondTop::every_variant() {
+            assert!(samples.contains(&variant), "{:?}", variant);
+        }
+
+        let samples = <(Option<Nested>, Result<bool, Top>, Request)>::sample_variants(9, 20);
+        assert_eq!(20, samples.len());
+    }
+
+    #[derive(EveryVariant, Debug, Clone)]
+    pub enum Uninhabited {}
+
+    #[derive(EveryVariant, Debug, Clone)]
+    pub enum PartlyInhabited {
+        Never(Uninhabited),
+        Always(bool),
+    }
+
+    #[test]
+    fn sample_uninhabited() {
+        assert!(Uninhabited::sample_variants(3, 10).is_empty());
+        assert!(<(bool, Uninhabited)>::sample_variants(3, 10).is_empty());
+        assert_eq!(10, Option::<Uninhabited>::sample_variants(3, 10).len());
+
+        let samples = PartlyInhabited::sample_variants(3, 10);
+        assert_eq!(10, samples.len());
+        assert!(samples
+            .iter()
+            .all(|s| matches!(s, PartlyInhabited::Always(_))));
+    }
+
+    #[test]
+    #[should_panic(expected = "panicked with seed 5: assertion")]
+    fn sampled_variants_report_seed() {
+        Nested::for_sampled_variants(5, 10, |n| assert_ne!(&Nested::Second, n));
+    }
+
+    #[test]
+    #[should_panic(expected = "with seed 5, rerun with EVERY_VARIANT_SEED=5: assertion")]
+    fn sampled_variants_from_env_report_variable() {
+        // no other test reads the variable
+        std::env::set_var(crate::random::SEED_VARIABLE, "5");
+        Nested::for_sampled_variants_from_env(10, |n| assert_ne!(&Nested::Second, n));
+    }
+
+    #[test]
+    fn nth_variant() {
+        check_types!(
+            check_nth:
+            (),
+            f64,
+            Option<Nested>,
+            Result<Top, bool>,
+            Vec<Nested>,
+            (u8, bool, Nested),
+            [Nested; 3],
+            [bool; 0],
+            crate::FullArray<Nested, 3>,
+            Message,
+            TestUnnamed1,
+            TestNamed1,
+            Generic2,
+            Request,
+            Route,
+            Worker,
+            Command,
+            Packet,
+            PartlyInhabited,
+            Uninhabited
+        );
+
+        // far too many to walk, but any of them can be picked out
+        let huge = Huge::nth_variant(40usize.pow(6) - 1).unwrap();
         assert_eq!(
-            u32::every_variant().len() * u32::every_variant().len(),
-            msgs
+            format!("{:?}", Message::every_variant().last().unwrap()),
+            format!("{:?}", huge.5)
+        );
+    }
+
+    #[test]
+    fn for_every_variant() {
+        let calls = std::cell::Cell::new(0);
+        String::for_every_variant(|_| calls.set(calls.get() + 1));
+        Option::<Nested>::for_every_variant(|_| calls.set(calls.get() + 1));
+        Message::for_every_variant(|_| calls.set(calls.get() + 1));
+        assert_eq!(
+            String::variant_count() + 4 + Message::variant_count(),
+            calls.get()
+        );
+
+        let mut seen = Vec::new();
+        Result::<bool, Nested>::for_every_variant_mut(|v| seen.push(v.clone()));
+        assert_eq!(Result::<bool, Nested>::every_variant(), seen);
+    }
+
+    #[test]
+    fn try_for_every_variant() {
+        use crate::VariantFailure;
+        use std::ops::ControlFlow;
+
+        assert_eq!(Ok(()), Top::try_for_every_variant(|_| Ok::<(), ()>(())));
+        assert_eq!(Ok(()), Top::try_for_every_variant(|_| ()));
+
+        let mut calls = 0;
+        let failure = Top::try_for_every_variant(|top| {
+            calls += 1;
+            match top {
+                Top::Nested(Nested::Second) => Err("second"),
+                _ => Ok(()),
+            }
+        });
+        assert_eq!(
+            Err(VariantFailure {
+                index: 2,
+                value: Top::Nested(Nested::Second),
+                error: "second"
+            }),
+            failure
+        );
+        assert_eq!(3, calls);
+        assert_eq!(
+            "variant 2 failed with \"second\": Nested(Second)",
+            failure.unwrap_err().to_string()
+        );
+
+        let stopped = Option::<u8>::try_for_every_variant(|v| match v {
+            Some(n) if *n > 0 => ControlFlow::Break(*n),
+            _ => ControlFlow::Continue(()),
+        });
+        assert_eq!(Some(1), stopped.err().map(|failure| failure.index));
+    }
+
+    #[test]
+    fn every_variant_shallow() {
+        check_types!(
+            check_shallow:
+            Option<Nested>,
+            Result<Top, bool>,
+            Vec<Nested>,
+            (u8, Top, Nested),
+            [Nested; 3],
+            [bool; 0],
+            crate::FullArray<Top, 3>,
+            Message,
+            Huge,
+            Route,
+            Command,
+            Packet
+        );
+
+        // one value with the first of everything, then one per other variant of each field
+        let messages = Message::every_variant_shallow();
+        let fields = [
+            String::every_variant_shallow().len(),
+            u32::every_variant_shallow().len(),
+            Option::<u64>::every_variant_shallow().len(),
+            Top::every_variant_shallow().len(),
+            SecondTop::every_variant_shallow().len(),
+        ];
+        assert_eq!(
+            1 + fields.iter().map(|len| len - 1).sum::<usize>(),
+            messages.len()
+        );
+        assert_eq!(
+            6 * (messages.len() - 1) + 1,
+            Huge::every_variant_shallow().len()
+        );
+        assert!(Uninhabited::every_variant_shallow().is_empty());
+        assert_eq!(
+            vec![
+                Top::One,
+                Top::Nested(Nested::First),
+                Top::Nested(Nested::Second)
+            ],
+            Top::every_variant_shallow()[..3]
+        );
+    }
+
+    #[test]
+    fn variant_index() {
+        check_types!(
+            check_index:
+            (),
+            f64,
+            String,
+            Option<Nested>,
+            Result<Top, bool>,
+            Vec<Nested>,
+            (u8, bool, Nested),
+            [Nested; 3],
+            [bool; 0],
+            crate::FullArray<Nested, 3>,
+            Message,
+            TestUnnamed1,
+            TestNamed1,
+            Generic2,
+            Request,
+            Route,
+            Worker,
+            Command,
+            Packet,
+            PartlyInhabited
+        );
+
+        let huge = Huge::nth_variant(40usize.pow(6) - 1).unwrap();
+        assert_eq!(Some(40usize.pow(6) - 1), huge.variant_index());
+
+        // values that are never generated have no index
+        assert_eq!(None, String::from("production").variant_index());
+        assert_eq!(None, Some(7u8).variant_index());
+        assert_eq!(None, Port(8080).variant_index());
+        assert_eq!(None, vec![Nested::Second, Nested::First].variant_index());
+        assert_eq!(None, (true, 7u8).variant_index());
+    }
+
+    #[test]
+    fn shard_variants() {
+        let mut indexes = Vec::new();
+        for shard in 0..3 {
+            indexes.extend(Message::shard_variants(shard, 3).map(|(index, _)| index));
+        }
+        assert_eq!((0..Message::variant_count()).collect::<Vec<_>>(), indexes);
+    }
+
+    #[derive(EveryVariant, Debug, Clone, PartialEq)]
+    #[every_variant(max_depth = 1)]
+    pub enum Expr {
+        Lit(bool),
+        Neg(Box<Expr>),
+        Add(Box<Expr>, Box<Expr>),
+    }
+
+    impl Expr {
+        fn depth(&self) -> usize {
+            match self {
+                Expr::Lit(_) => 0,
+                Expr::Neg(e) => 1 + e.depth(),
+                Expr::Add(a, b) => 1 + a.depth().max(b.depth()),
+            }
+        }
+    }
+
+    // mutually recursive, through vectors, with the default depth
+    #[derive(EveryVariant, Debug, Clone, PartialEq)]
+    pub enum Stmt {
+        Empty,
+        Block(Block),
+    }
+
+    #[derive(EveryVariant, Debug, Clone, PartialEq)]
+    pub struct Block {
+        pub stmts: Vec<Stmt>,
+        label: Option<Nested>,
+    }
+
+    #[test]
+    fn recursive_types() {
+        // two literals, then a negation or a sum of those
+        let exprs = Expr::every_variant();
+        assert_eq!(2 + 2 + 2 * 2, exprs.len());
+        assert_eq!(exprs.len(), Expr::variant_count());
+        assert_eq!(Expr::Lit(true), exprs[0]);
+        assert_eq!(1, exprs.iter().map(Expr::depth).max().unwrap());
+        assert!(Box::<Expr>::every_variant_iter().all(|e| e.depth() == 1 || e.depth() == 0));
+
+        check_types!(check_nth: Expr, Box<Expr>, Stmt, Block);
+        check_types!(check_index: Expr, Box<Expr>, Stmt, Block);
+        check_types!(check_shallow: Expr);
+
+        // the vectors in shallow values hold shallow values, those are not in every_variant
+        let mut coverage = crate::VariantCoverage::<Stmt>::new();
+        Stmt::every_variant_shallow()
+            .iter()
+            .for_each(|stmt| coverage.record(stmt));
+        coverage.assert_complete();
+
+        // too deep to have been generated
+        let deep = Expr::Neg(Box::new(Expr::Neg(Box::new(Expr::Lit(true)))));
+        assert_eq!(None, deep.variant_index());
+
+        let blocks = Block::every_variant();
+        assert_eq!(blocks.len(), Block::variant_count());
+        assert_eq!(4 * 4, blocks.len());
+        assert_eq!(1 + 4 * 4, Stmt::variant_count());
+        assert!(Expr::every_pair().len() <= exprs.len());
+        assert_eq!(3, Expr::sample_variants(3, 3).len());
+        assert_eq!(0, crate::depth::current_depth::<Expr>());
+    }
+
+    #[derive(EveryVariant, Debug, Clone)]
+    pub enum Tree {
+        Leaf,
+        Branch(Vec<Forest>),
+    }
+
+    #[derive(EveryVariant, Debug, Clone)]
+    pub struct Forest {
+        trees: Vec<Tree>,
+    }
+
+    #[test]
+    fn repeated_variants() {
+        // every `Vec<()>` holds `vec![()]` twice, so the element indexes are not ascending
+        let vecs = Vec::<Vec<()>>::every_variant();
+        assert_eq!(Some(0), vecs[0].variant_index());
+        assert_eq!(Some(2), vec![vec![()]].variant_index());
+
+        check_types!(
+            check_index:
+            Vec<()>,
+            Vec<Vec<()>>,
+            Vec<Option<Vec<()>>>,
+            std::collections::BTreeSet<Vec<()>>,
+            std::collections::BTreeMap<Vec<()>, Vec<()>>,
+            [Vec<()>; 3],
+            crate::FullArray<Vec<()>, 2>,
+            Tree,
+            Forest,
+            Vec<Tree>
+        );
+    }
+
+    /// A type of its own named `Some` and `None`, in place of the ones from the prelude
+    mod shadowed_prelude {
+        use self::Maybe::*;
+        use crate::EveryVariant;
+
+        #[derive(EveryVariant, Debug, Clone, PartialEq)]
+        pub enum Maybe {
+            None,
+            Some(bool),
+        }
+
+        #[test]
+        fn derives() {
+            assert_eq!(vec![None, Some(true), Some(false)], Maybe::every_variant());
+            assert_eq!(Some(false), Maybe::nth_variant(2).unwrap());
+            assert_eq!(Option::Some(1), Some(true).variant_index());
+            assert!(Maybe::random_variant(&mut crate::Rng::new(1)).is_some());
+            assert_eq!(3, Maybe::every_pair().len());
+            assert_eq!(3, Maybe::every_variant_shallow().len());
+            let mut paths = Vec::new();
+            Some(true).observed_variant_paths("", &mut paths);
+            assert_eq!(vec!["Maybe::Some"], paths);
+        }
+    }
+
+    #[derive(EveryVariant, Debug, Clone)]
+    pub struct EmptyNamed {}
+
+    #[derive(EveryVariant, Debug, Clone)]
+    pub struct EmptyUnnamed();
+
+    #[derive(EveryVariant, Debug, Clone)]
+    pub enum EmptyVariants {
+        Braces {},
+        Parens(),
+        Unit,
+    }
+
+    #[test]
+    fn empty_fields() {
+        assert!(matches!(EmptyNamed::every_variant()[..], [EmptyNamed {}]));
+        assert!(matches!(
+            EmptyUnnamed::every_variant()[..],
+            [EmptyUnnamed()]
+        ));
+        assert!(matches!(
+            EmptyVariants::every_variant()[..],
+            [
+                EmptyVariants::Braces {},
+                EmptyVariants::Parens(),
+                EmptyVariants::Unit
+            ]
+        ));
+        assert_eq!(Some(1), EmptyVariants::Parens().variant_index());
+        check_types!(check_index: EmptyNamed, EmptyUnnamed, EmptyVariants);
+    }
+
+    #[test]
+    fn leaf_variants_built_once() {
+        use super::CachedVariants;
+        use crate::profile::exhaustively;
+
+        // the exhaustive strings end with one of 64 KiB, which is only built the first time
+        let strings = || exhaustively(String::cached_variants).as_ptr();
+        assert_eq!(strings(), strings());
+        assert_ne!(strings(), String::cached_variants().as_ptr());
+
+        exhaustively(|| {
+            let last = String::variant_count() - 1;
+            let long = String::nth_variant(last).unwrap();
+            assert!(long.len() > usize::from(u16::MAX));
+            assert_eq!(Some(last), long.variant_index());
+            assert_eq!(None, String::nth_variant(last + 1));
+        });
+    }
+
+    #[derive(EveryVariant, Debug, Clone, PartialEq)]
+    pub struct Shared {
+        name: std::sync::Arc<str>,
+        path: Box<str>,
+        bytes: Box<[bool]>,
+        top: std::rc::Rc<Top>,
+        label: std::borrow::Cow<'static, str>,
+        mode: std::borrow::Cow<'static, Nested>,
+    }
+
+    #[test]
+    fn pointers() {
+        use std::borrow::Cow;
+        use std::rc::Rc;
+        use std::sync::Arc;
+
+        check_types!(
+            check_nth:
+            Box<Top>,
+            Rc<Top>,
+            Arc<Top>,
+            Box<str>,
+            Rc<str>,
+            Arc<str>,
+            Box<[Nested]>,
+            Arc<[Nested]>,
+            Cow<'static, str>,
+            Cow<'static, Nested>,
+            Shared
+        );
+        check_types!(
+            check_index:
+            Box<Top>,
+            Rc<Top>,
+            Arc<Top>,
+            Box<str>,
+            Arc<str>,
+            Box<[Nested]>,
+            Rc<[Nested]>,
+            Cow<'static, str>,
+            Cow<'static, Nested>,
+            Shared
+        );
+        assert_eq!(
+            String::every_variant(),
+            Arc::<str>::every_variant()
+                .iter()
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Vec::<Nested>::every_variant(),
+            Box::<[Nested]>::every_variant()
+                .into_iter()
+                .map(Vec::from)
+                .collect::<Vec<_>>()
+        );
+
+        // both forms of every variant
+        let modes = Cow::<'static, Nested>::every_variant();
+        assert_eq!(2 * Nested::variant_count(), modes.len());
+        assert!(matches!(modes[0], Cow::Borrowed(Nested::First)));
+        assert!(matches!(modes[3], Cow::Owned(Nested::First)));
+        assert!(matches!(
+            Cow::<'static, str>::every_variant()[..],
+            [Cow::Borrowed(_), Cow::Owned(_)]
+        ));
+
+        // the borrowed values are leaked once, not every time they are generated
+        let borrowed = |cow: Cow<'static, Nested>| match cow {
+            Cow::Borrowed(value) => value as *const Nested,
+            Cow::Owned(_) => panic!("expected a borrowed value"),
+        };
+        let again = Cow::<'static, Nested>::every_variant();
+        assert_eq!(borrowed(modes[1].clone()), borrowed(again[1].clone()));
+        assert_eq!(
+            borrowed(modes[2].clone()),
+            borrowed(Cow::nth_variant(2).unwrap())
+        );
+        let elsewhere = std::thread::spawn(|| match Cow::<'static, Nested>::nth_variant(1) {
+            Some(Cow::Borrowed(value)) => value as *const Nested as usize,
+            _ => panic!("expected a borrowed value"),
+        });
+        assert_eq!(
+            borrowed(modes[1].clone()) as usize,
+            elsewhere.join().unwrap()
+        );
+
+        let mut coverage = crate::VariantCoverage::<Shared>::new();
+        coverage.record(&Shared::every_variant()[0]);
+        assert_eq!(
+            vec![
+                "top.Top::Nested",
+                "top.Top::Nested.0.Nested::First",
+                "top.Top::Nested.0.Nested::Second",
+                "top.Top::Nested.0.Nested::Third",
+                "label.Cow::Owned",
+                "mode.Cow::Borrowed.0.Nested::Second",
+                "mode.Cow::Borrowed.0.Nested::Third",
+                "mode.Cow::Owned",
+            ],
+            coverage.unobserved()[..8]
+        );
+    }
+
+    // recursive through the values of a map
+    #[derive(EveryVariant, Debug, Clone, PartialEq)]
+    pub struct Dir {
+        children: std::collections::BTreeMap<bool, Dir>,
+        kind: Nested,
+    }
+
+    /// Every value is equal to every other, so only the variants tell them apart
+    #[derive(EveryVariant, Debug, Clone)]
+    pub struct Tied(bool);
+
+    impl PartialEq for Tied {
+        fn eq(&self, _: &Self) -> bool {
+            true
+        }
+    }
+
+    impl Eq for Tied {}
+
+    impl PartialOrd for Tied {
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Tied {
+        fn cmp(&self, _: &Self) -> std::cmp::Ordering {
+            std::cmp::Ordering::Equal
+        }
+    }
+
+    #[test]
+    fn collections() {
+        use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, VecDeque};
+
+        check_types!(
+            check_nth:
+            BTreeSet<bool>,
+            BTreeSet<()>,
+            BTreeMap<bool, Nested>,
+            BTreeMap<(), Top>,
+            BTreeMap<bool, Uninhabited>,
+            VecDeque<Nested>,
+            BinaryHeap<bool>,
+            Dir
+        );
+        check_types!(
+            check_index:
+            BTreeSet<bool>,
+            BTreeSet<()>,
+            BTreeMap<bool, Nested>,
+            BTreeMap<(), Top>,
+            VecDeque<Nested>,
+            BinaryHeap<bool>,
+            BinaryHeap<Tied>,
+            Dir
+        );
+        check_types!(
+            check_index_eq:
+            HashSet<Top>,
+            HashMap<Nested, bool>,
+            HashMap<String, Option<Nested>>
+        );
+
+        // the elements of a heap are told apart by their variants, not by `==`
+        assert_eq!(Some(2), BinaryHeap::from(vec![Tied(true)]).variant_index());
+        assert_eq!(None, BinaryHeap::from(vec![Tied(false)]).variant_index());
+
+        let sets = HashSet::<Nested>::every_variant();
+        assert_eq!(3, sets.len());
+        assert_eq!(3, sets[0].len());
+        assert!(sets[1].is_empty());
+        assert_eq!(1, sets[2].len());
+
+        // every value shows up, and the keys of a map are all different
+        let maps = HashMap::<Nested, Top>::every_variant();
+        assert_eq!(1 + Top::variant_count() + 1, maps.len());
+        assert!(maps[0].is_empty());
+        let values: Vec<&Top> = maps[1..5].iter().flat_map(|m| m.values()).collect();
+        assert_eq!(Top::every_variant().iter().collect::<Vec<_>>(), values);
+        assert_eq!(
+            HashSet::<Nested>::every_variant()[0],
+            maps[5].keys().cloned().collect()
+        );
+        assert_eq!(
+            None,
+            std::iter::once((Nested::Second, Top::One))
+                .collect::<HashMap<_, _>>()
+                .variant_index()
+        );
+
+        let mut coverage = crate::VariantCoverage::<HashMap<String, Option<Nested>>>::new();
+        HashMap::<String, Option<Nested>>::for_every_variant_mut(|map| coverage.record(map));
+        coverage.assert_complete();
+        assert_eq!(Some("values.Option::None"), coverage.paths().next());
+
+        assert_eq!(Dir::every_variant().len(), Dir::variant_count());
+    }
+
+    #[derive(EveryVariant, Debug, Clone, PartialEq)]
+    pub struct Frame {
+        #[every_variant(edge_cases)]
+        len: u16,
+        #[every_variant(edge_cases)]
+        offset: std::num::NonZeroI8,
+        kind: Option<Nested>,
+    }
+
+    #[test]
+    fn edge_cases_attribute() {
+        use crate::EdgeCases;
+
+        let lens = u16::edge_cases().len();
+        let offsets = std::num::NonZeroI8::edge_cases().len();
+        assert_eq!(lens * offsets * 4, Frame::variant_count());
+        check_types!(check_nth: Frame);
+        check_types!(check_index: Frame);
+
+        let frames = Frame::every_variant();
+        assert!(frames.iter().any(|f| f.len == u16::MAX));
+        assert!(frames.iter().any(|f| f.offset.get() == i8::MIN));
+        assert_eq!(
+            1 + (lens - 1) + (offsets - 1) + 3,
+            Frame::every_variant_shallow().len()
         );
     }
+
+    #[derive(EveryVariant, Debug, Clone, PartialEq)]
+    pub struct Reading {
+        #[every_variant(edge_cases)]
+        value: f64,
+        unit: Option<Nested>,
+    }
+
+    #[test]
+    fn float_edge_cases() {
+        use crate::variant_eq;
+
+        check_types!(check_index: Reading, crate::EdgeCase<f32>);
+
+        let readings = Reading::every_variant();
+        let nan = readings.iter().find(|r| r.value.is_nan()).unwrap();
+        assert_ne!(nan, nan);
+        assert!(variant_eq(nan, nan));
+        for (index, reading) in readings.iter().enumerate() {
+            assert!(variant_eq(reading, &Reading::nth_variant(index).unwrap()));
+        }
+
+        let zero = Reading {
+            value: 0.0,
+            unit: None,
+        };
+        let negative_zero = Reading {
+            value: -0.0,
+            unit: None,
+        };
+        assert_eq!(zero, negative_zero);
+        assert!(!variant_eq(&zero, &negative_zero));
+        // values that are not generated are compared with `==`
+        let odd = Reading {
+            value: 2.5,
+            unit: None,
+        };
+        assert!(variant_eq(&odd, &odd.clone()));
+        assert!(variant_eq(&2.5f64, &2.5f64));
+        assert!(!variant_eq(&odd, &zero));
+    }
+
+    #[derive(EveryVariant, Debug, Clone, PartialEq)]
+    pub struct Sample {
+        value: f64,
+        scale: Option<f32>,
+    }
+
+    #[test]
+    fn plain_float_fields() {
+        use crate::{profile, variant_eq};
+
+        // the float fields only get their edge cases in the exhaustive profile
+        let samples = Sample::every_variant_exhaustive();
+        assert!(samples.iter().any(|s| s.value.is_nan()));
+        assert!(samples.iter().any(|s| s.scale.is_some_and(f32::is_nan)));
+        for (index, sample) in samples.iter().enumerate() {
+            assert!(variant_eq(sample, sample), "{:?}", sample);
+            assert_eq!(
+                Some(index),
+                profile::exhaustively(|| sample.variant_index())
+            );
+        }
+        assert!(!variant_eq(&samples[0], &samples[1]));
+        assert_eq!(Some(1), (-32f64).variant_index());
+        assert_eq!(None, f64::NAN.variant_index());
+    }
+
+    #[derive(EveryVariant, Debug, Clone)]
+    pub enum Sensor {
+        Off,
+        Named {
+            name: String,
+            #[every_variant(values = [1, 2])]
+            bus: u8,
+        },
+        Measuring(Reading),
+        Chain(Vec<Sensor>),
+    }
+
+    #[test]
+    fn profiles() {
+        use crate::{Exhaustive, Minimal, Standard};
+
+        let debug = |sensors: Vec<Sensor>| -> Vec<String> {
+            sensors.iter().map(|s| format!("{:?}", s)).collect()
+        };
+        let minimal = debug(Sensor::every_variant_in::<Minimal>());
+        let standard = debug(Sensor::every_variant_in::<Standard>());
+        let exhaustive = debug(Sensor::every_variant_in::<Exhaustive>());
+        assert_eq!(debug(Sensor::every_variant_shallow()), minimal);
+        assert_eq!(debug(Sensor::every_variant()), standard);
+        assert!(minimal.len() < standard.len() && standard.len() < exhaustive.len());
+        // except for the vector holding every variant, which holds every exhaustive value instead
+        assert!(standard
+            .iter()
+            .filter(|s| !s.starts_with("Chain"))
+            .all(|s| exhaustive.contains(s)));
+
+        // the strings get their edge cases, the listed values stay the same
+        let sensors = Sensor::every_variant_exhaustive();
+        let names = String::every_variant_exhaustive().len();
+        let named = sensors
+            .iter()
+            .filter(|s| matches!(s, Sensor::Named { .. }))
+            .count();
+        assert_eq!(names * 2, named);
+        assert!(sensors
+            .iter()
+            .all(|s| !matches!(s, Sensor::Named { bus, .. } if *bus > 2)));
+
+        // down through the vector and back into the type itself
+        assert!(sensors.iter().any(|s| match s {
+            Sensor::Chain(chain) => chain
+                .iter()
+                .any(|s| matches!(s, Sensor::Named { name, .. } if name.is_empty())),
+            _ => false,
+        }));
+    }
+
+    #[test]
+    fn exhaustive_one_at_a_time() {
+        use crate::profile::{self, Exhaustive, Profile};
+
+        let exhaustive: Vec<String> = Sensor::every_variant_exhaustive_iter()
+            .map(|s| format!("{:?}", s))
+            .collect();
+        assert_eq!(exhaustive.len(), Exhaustive::variant_count::<Sensor>());
+        // outside the scope the values are the usual ones again
+        assert!(!profile::is_exhaustive());
+        assert_eq!(2, f64::variant_count());
+
+        for index in [0, 1, exhaustive.len() / 2, exhaustive.len() - 1] {
+            let sensor = Exhaustive::nth_variant::<Sensor>(index).unwrap();
+            assert_eq!(exhaustive[index], format!("{:?}", sensor));
+        }
+        assert!(Exhaustive::nth_variant::<Sensor>(exhaustive.len()).is_none());
+
+        let sharded: Vec<String> = (0..3)
+            .flat_map(|shard| Exhaustive::shard_variants::<Sensor>(shard, 3))
+            .map(|(_, s)| format!("{:?}", s))
+            .collect();
+        assert_eq!(exhaustive, sharded);
+
+        let mut seen = 0;
+        Sensor::for_every_variant_in::<Exhaustive, _>(|_| seen += 1);
+        assert_eq!(exhaustive.len(), seen);
+
+        let failures = Sensor::par_for_every_variant_in::<Exhaustive, _, _>(|s| match s {
+            Sensor::Named { name, .. } if name.is_empty() => Err(()),
+            _ => Ok(()),
+        })
+        .unwrap_err();
+        assert_eq!(2, failures.len());
+        assert!(failures
+            .iter()
+            .all(|failure| exhaustive[failure.index] == format!("{:?}", failure.value)));
+    }
 }
@@ -0,0 +1,222 @@
+//! Recording which enum variants of a type show up at runtime, to check that tests exercise
+//! every one of them.
+
+use crate::EveryVariant;
+use std::collections::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Counts how often every enum variant in `T`, at every level of nesting, was seen in the
+/// values passed to `record`
+pub struct VariantCoverage<T> {
+    /// Every variant path of `T`, in the order of `T::variant_paths`
+    paths: Vec<String>,
+    /// How often each path was observed
+    counts: HashMap<String, usize>,
+    /// The number of values recorded
+    recorded: usize,
+    _type: PhantomData<fn(&T)>,
+}
+
+impl<T: EveryVariant> VariantCoverage<T> {
+    /// A collector in which no variant has been observed yet
+    pub fn new() -> Self {
+        let mut paths = Vec::new();
+        T::variant_paths("", &mut paths);
+        let counts = paths.iter().map(|path| (path.clone(), 0)).collect();
+
+        VariantCoverage {
+            paths,
+            counts,
+            recorded: 0,
+            _type: PhantomData,
+        }
+    }
+
+    /// Marks the variants `value` is made of as observed
+    pub fn record(&mut self, value: &T) {
+        let mut observed = Vec::new();
+        value.observed_variant_paths("", &mut observed);
+        for path in observed {
+            *self.counts.entry(path).or_insert(0) += 1;
+        }
+        self.recorded += 1;
+    }
+
+    /// The number of values recorded
+    pub fn recorded(&self) -> usize {
+        self.recorded
+    }
+
+    /// How often the variant at `path` was observed
+    pub fn count(&self, path: &str) -> usize {
+        self.counts.get(path).copied().unwrap_or(0)
+    }
+
+    /// Every variant path of `T`
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.paths.iter().map(String::as_str)
+    }
+
+    /// The variant paths that were never observed
+    pub fn unobserved(&self) -> Vec<&str> {
+        self.paths().filter(|path| self.count(path) == 0).collect()
+    }
+
+    /// Whether every variant was observed at least once
+    pub fn is_complete(&self) -> bool {
+        self.paths().all(|path| self.count(path) > 0)
+    }
+
+    /// Panics with the report when a variant was never observed
+    #[track_caller]
+    pub fn assert_complete(&self) {
+        assert!(self.is_complete(), "{}", self);
+    }
+}
+
+impl<T: EveryVariant> Default for VariantCoverage<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: EveryVariant> fmt::Display for VariantCoverage<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let unobserved = self.unobserved();
+        write!(
+            f,
+            "{} of {} variants of {} observed in {} values",
+            self.paths.len() - unobserved.len(),
+            self.paths.len(),
+            std::any::type_name::<T>(),
+            self.recorded
+        )?;
+        for path in unobserved {
+            write!(f, "\n  never observed: {}", path)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> fmt::Debug for VariantCoverage<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("VariantCoverage")
+            .field("paths", &self.paths)
+            .field("counts", &self.counts)
+            .field("recorded", &self.recorded)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+
+    #[derive(EveryVariant, Debug, Clone)]
+    enum Shape {
+        Point,
+        Circle(Option<Unit>),
+        Rect {
+            unit: Unit,
+            filled: bool,
+        },
+        #[every_variant(skip)]
+        Custom(String),
+    }
+
+    #[derive(EveryVariant, Debug, Clone, PartialEq)]
+    enum Unit {
+        Px,
+        Em,
+    }
+
+    #[derive(EveryVariant, Debug, Clone)]
+    struct Drawing {
+        shapes: Vec<Shape>,
+        #[every_variant(values = [Unit::Px])]
+        grid: Unit,
+        layer: (bool, Unit),
+    }
+
+    #[test]
+    fn paths() {
+        let coverage = VariantCoverage::<Shape>::new();
+        let paths: Vec<&str> = coverage.paths().collect();
+        assert_eq!(
+            vec![
+                "Shape::Point",
+                "Shape::Circle",
+                "Shape::Circle.0.Option::None",
+                "Shape::Circle.0.Option::Some",
+                "Shape::Circle.0.Option::Some.0.Unit::Px",
+                "Shape::Circle.0.Option::Some.0.Unit::Em",
+                "Shape::Rect",
+                "Shape::Rect.unit.Unit::Px",
+                "Shape::Rect.unit.Unit::Em",
+            ],
+            paths
+        );
+
+        // elements of vectors share a path, fields with listed values are left out
+        let coverage = VariantCoverage::<Drawing>::new();
+        let paths: Vec<&str> = coverage.paths().collect();
+        assert_eq!("shapes.Shape::Point", paths[0]);
+        assert_eq!(
+            vec!["layer.1.Unit::Px", "layer.1.Unit::Em"],
+            paths[paths.len() - 2..]
+        );
+        assert_eq!(11, paths.len());
+    }
+
+    #[test]
+    fn records_observed_variants() {
+        let mut coverage = VariantCoverage::<Shape>::new();
+        assert_eq!(9, coverage.unobserved().len());
+
+        coverage.record(&Shape::Circle(Some(Unit::Em)));
+        coverage.record(&Shape::Circle(None));
+        coverage.record(&Shape::Custom("star".into()));
+        assert_eq!(3, coverage.recorded());
+        assert_eq!(2, coverage.count("Shape::Circle"));
+        assert_eq!(
+            vec![
+                "Shape::Point",
+                "Shape::Circle.0.Option::Some.0.Unit::Px",
+                "Shape::Rect",
+                "Shape::Rect.unit.Unit::Px",
+                "Shape::Rect.unit.Unit::Em",
+            ],
+            coverage.unobserved()
+        );
+        assert!(!coverage.is_complete());
+        assert_eq!(
+            format!(
+                "4 of 9 variants of {} observed in 3 values\n  never observed: Shape::Point",
+                std::any::type_name::<Shape>()
+            ),
+            format!("{}", coverage)
+                .lines()
+                .take(2)
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+
+        for shape in Shape::every_variant_iter() {
+            coverage.record(&shape);
+        }
+        coverage.assert_complete();
+    }
+
+    #[test]
+    #[should_panic(expected = "never observed: layer.1.Unit::Em")]
+    fn incomplete_coverage_panics() {
+        let mut coverage = VariantCoverage::<Drawing>::new();
+        for mut drawing in Drawing::every_variant() {
+            drawing.layer.1 = Unit::Px;
+            coverage.record(&drawing);
+        }
+        coverage.assert_complete();
+    }
+}
@@ -6,4 +6,8 @@ impl EveryVariant for Uuid {
     fn every_variant() -> Vec<Self> {
         vec![Uuid::default()]
     }
+
+    fn variant_index(&self) -> Option<usize> {
+        crate::position_of(self)
+    }
 }
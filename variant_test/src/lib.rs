@@ -0,0 +1,9 @@
+//! Functions crashtested from outside the crate, by the tests in `tests/`
+
+use every_variant::*;
+
+/// Parses a port number, falling back to `default` and then to 80
+#[crashtest_every_variant]
+pub fn parse_port(#[every_variant(edge_cases)] port: &str, default: Option<u16>) -> u16 {
+    port.trim().parse().ok().or(default).unwrap_or(80)
+}
@@ -43,11 +43,13 @@ mod test {
     fn messages_number() {
         let msgs = Message::every_variant().len();
         assert_eq!(40, msgs);
+        assert_eq!(40, Message::variant_count());
     }
 
     #[test]
     fn opts_number() {
         let msgs = Option::<u64>::every_variant().len();
         assert_eq!(2, msgs);
+        assert_eq!(2, Option::<u64>::variant_count());
     }
 }
@@ -0,0 +1,6 @@
+use every_variant::*;
+
+// functions of another crate are listed by their crate name
+crashtests! {
+    variant_test::parse_port,
+}